@@ -1,10 +1,95 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
 use std::sync::Arc;
 
 use anyhow::Result;
 
+use derive_builder::Builder;
+
 use crate::browser::tab::Tab;
+use crate::browser::tab::network::NetworkConditions;
 use crate::protocol::cdp::Target::CreateTarget;
 
+/// Options for `Browser::new_context_with_options`.
+#[derive(Clone, Default, Builder, PartialEq, Eq)]
+#[allow(clippy::struct_field_names)] // named after Target.createBrowserContext's parameters
+pub struct ContextOptions {
+    /// The proxy server this context's requests go through, e.g. `http://proxy.example:3128` or
+    /// `socks5://127.0.0.1:1080`, instead of the browser's.
+    #[builder(default = "None")]
+    pub proxy_server: Option<String>,
+
+    /// Hosts which are reached without the proxy, separated by commas, e.g.
+    /// `*.internal,localhost`.
+    #[builder(default = "None")]
+    pub proxy_bypass_list: Option<String>,
+
+    /// The username and password to answer the proxy's authentication challenges with, in
+    /// every page of the context.
+    #[builder(default = "None")]
+    pub proxy_credentials: Option<(String, String)>,
+}
+
+impl ContextOptions {
+    pub fn default_builder() -> ContextOptionsBuilder {
+        ContextOptionsBuilder::default()
+    }
+}
+
+impl Debug for ContextOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("ContextOptions")
+            .field("proxy_server", &self.proxy_server)
+            .field("proxy_bypass_list", &self.proxy_bypass_list)
+            .field(
+                "proxy_credentials",
+                &redacted_credentials(self.proxy_credentials.as_ref()),
+            )
+            .finish()
+    }
+}
+
+/// Proxy credentials as they may be logged: the username, but not the password.
+fn redacted_credentials(credentials: Option<&(String, String)>) -> Option<(&str, &str)> {
+    credentials.map(|(username, _)| (username.as_str(), "<redacted>"))
+}
+
+/// The settings of each context which every page opened in it gets, be it with
+/// `Context::new_tab` or by another page of the context, e.g. with `window.open`.
+#[derive(Clone, Default)]
+pub(crate) struct ContextSettings {
+    network_conditions: Option<NetworkConditions>,
+    proxy_credentials: Option<(String, String)>,
+}
+
+/// `ContextSettings` by browser context ID.
+pub(crate) type ContextSettingsById = HashMap<String, ContextSettings>;
+
+impl Debug for ContextSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("ContextSettings")
+            .field("network_conditions", &self.network_conditions)
+            .field(
+                "proxy_credentials",
+                &redacted_credentials(self.proxy_credentials.as_ref()),
+            )
+            .finish()
+    }
+}
+
+impl ContextSettings {
+    /// Applies these settings to `tab`, which has just been opened.
+    pub(crate) fn apply(&self, tab: &Tab) -> Result<()> {
+        if let Some(conditions) = self.network_conditions.clone() {
+            tab.emulate_network(conditions)?;
+        }
+        if self.proxy_credentials.is_some() {
+            tab.set_proxy_credentials(self.proxy_credentials.clone())?;
+        }
+        Ok(())
+    }
+}
+
 /// Equivalent to a new incognito window
 pub struct Context<'a> {
     id: String,
@@ -13,6 +98,25 @@ pub struct Context<'a> {
 
 impl<'a> Context<'a> {
     pub fn new(browser: &'a super::Browser, context_id: String) -> Self {
+        Self::with_options(browser, context_id, &ContextOptions::default())
+    }
+
+    pub(crate) fn with_options(
+        browser: &'a super::Browser,
+        context_id: String,
+        options: &ContextOptions,
+    ) -> Self {
+        if options.proxy_credentials.is_some() {
+            browser
+                .inner
+                .context_settings
+                .lock()
+                .unwrap()
+                .entry(context_id.clone())
+                .or_default()
+                .proxy_credentials
+                .clone_from(&options.proxy_credentials);
+        }
         Self {
             id: context_id,
             browser,
@@ -21,6 +125,10 @@ impl<'a> Context<'a> {
 
     /// Opens a new tab in this context. It will not share cookies or a cache with the default
     /// browsing context or any other contexts created
+    ///
+    /// If the context has proxy credentials, the tab (like any other page of the context)
+    /// answers the proxy's authentication challenges with them, which pauses its requests
+    /// with the fetch domain.
     pub fn new_tab(&self) -> Result<Arc<Tab>> {
         let tab_in_context = CreateTarget {
             url: "about:blank".to_string(),
@@ -36,9 +144,27 @@ impl<'a> Context<'a> {
             for_tab: None,
             hidden: None,
         };
+        // the browser applies the context's settings before handing out the tab
         self.browser.new_tab_with_options(tab_in_context)
     }
 
+    /// Emulates `conditions` (see `Tab::emulate_network`) in every tab of this context,
+    /// including the ones opened later on with `new_tab` or by the context's pages.
+    pub fn emulate_network(&self, conditions: NetworkConditions) -> Result<&Self> {
+        self.browser
+            .inner
+            .context_settings
+            .lock()
+            .unwrap()
+            .entry(self.id.clone())
+            .or_default()
+            .network_conditions = Some(conditions.clone());
+        for tab in self.get_tabs()? {
+            tab.emulate_network(conditions.clone())?;
+        }
+        Ok(self)
+    }
+
     /// The BrowserContextId associated with this context
     pub fn get_id(&self) -> &str {
         &self.id
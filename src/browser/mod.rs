@@ -5,7 +5,7 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use anyhow::{Result, anyhow};
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 
 use process::Process;
 pub use process::{DEFAULT_ARGS, LaunchOptions, LaunchOptionsBuilder};
@@ -19,7 +19,7 @@ use crate::protocol::cdp::{
     self, Browser as B, Target, Target::GetTargets, types::Event, types::Method,
 };
 
-use crate::browser::context::Context;
+use crate::browser::context::{Context, ContextOptions, ContextSettingsById};
 use crate::util;
 use B::GetVersion;
 pub use B::GetVersionReturnObject;
@@ -85,6 +85,7 @@ pub struct BrowserInner {
     process: Option<Process>,
     transport: Arc<Transport>,
     tabs: Arc<Mutex<Vec<Arc<Tab>>>>,
+    context_settings: Arc<Mutex<ContextSettingsById>>,
     loop_shutdown_tx: mpsc::SyncSender<()>,
     close_on_drop: bool,
 }
@@ -164,6 +165,7 @@ impl Browser {
             inner: Arc::new(BrowserInner {
                 process,
                 tabs,
+                context_settings: Arc::default(),
                 transport,
                 loop_shutdown_tx: shutdown_tx,
                 close_on_drop,
@@ -318,17 +320,47 @@ impl Browser {
 
     /// Creates the equivalent of a new incognito window, AKA a browser context
     pub fn new_context(&self) -> Result<context::Context<'_>> {
+        self.new_context_with_options(ContextOptions::default())
+    }
+
+    /// Creates a browser context (see `new_context`) with its own proxy settings, so that
+    /// different contexts of the same browser can use different proxies.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// use headless_chrome::Browser;
+    /// use headless_chrome::browser::context::ContextOptions;
+    ///
+    /// let browser = Browser::default()?;
+    /// let context = browser.new_context_with_options(
+    ///     ContextOptions::default_builder()
+    ///         .proxy_server(Some("http://proxy.example:3128".to_string()))
+    ///         .proxy_credentials(Some(("user".to_string(), "secret".to_string())))
+    ///         .build()?,
+    /// )?;
+    /// let tab = context.new_tab()?;
+    /// tab.navigate_to("https://example.com")?.wait_until_navigated()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_context_with_options(
+        &self,
+        options: ContextOptions,
+    ) -> Result<context::Context<'_>> {
         debug!("Creating new browser context");
         let context_id = self
             .call_method(Target::CreateBrowserContext {
                 dispose_on_detach: None,
-                proxy_server: None,
-                proxy_bypass_list: None,
+                proxy_server: options.proxy_server.clone(),
+                proxy_bypass_list: options.proxy_bypass_list.clone(),
                 origins_with_universal_network_access: None,
             })?
             .browser_context_id;
         debug!("Created new browser context: {context_id:?}");
-        Ok(Context::new(self, context_id))
+        Ok(Context::with_options(self, context_id, &options))
     }
 
     /// Adds tabs that have not been opened with new_tab to the list of tabs
@@ -390,6 +422,7 @@ impl Browser {
         idle_browser_timeout: Duration,
     ) {
         let tabs = Arc::clone(&self.inner.tabs);
+        let context_settings = Arc::clone(&self.inner.context_settings);
         let transport = Arc::clone(&self.inner.transport);
 
         std::thread::spawn(move || {
@@ -428,8 +461,21 @@ impl Browser {
                                 // meaning the devtools has ben opened automatically..
                                 // for now ignoring devtools tabs to be in tabs..
                                 if target_info.Type == "page" {
+                                    let settings =
+                                        target_info.browser_context_id.as_ref().and_then(|id| {
+                                            context_settings.lock().unwrap().get(id).cloned()
+                                        });
                                     match Tab::new(target_info, Arc::clone(&transport)) {
                                         Ok(new_tab) => {
+                                            // before the tab can be found, so that
+                                            // `Context::new_tab` returns it set up
+                                            if let Some(settings) = settings {
+                                                if let Err(error) = settings.apply(&new_tab) {
+                                                    warn!(
+                                                        "Failed to apply context settings to new tab: {error}"
+                                                    );
+                                                }
+                                            }
                                             tabs.lock().unwrap().push(Arc::new(new_tab));
                                         }
                                         Err(_tab_creation_err) => {
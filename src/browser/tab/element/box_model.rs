@@ -1,3 +1,5 @@
+use serde::Deserialize;
+
 use crate::protocol::cdp::Page;
 
 use crate::browser::tab::point::Point;
@@ -196,3 +198,22 @@ impl BoxModel {
         }
     }
 }
+
+/// An element's border box in CSS pixels, relative to the viewport, as returned by
+/// `Element::bounding_box`.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct BoundingBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl BoundingBox {
+    pub fn midpoint(&self) -> Point {
+        Point {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        }
+    }
+}
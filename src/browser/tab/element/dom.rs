@@ -0,0 +1,160 @@
+use anyhow::Result;
+
+use log::debug;
+
+use crate::browser::tab::NoElementFound;
+use crate::protocol::cdp::DOM;
+
+use super::Element;
+
+impl<'a> Element<'a> {
+    /// This element's parent element, or `None` for the root element (and detached elements).
+    pub fn parent(&self) -> Result<Option<Element<'a>>> {
+        self.related_element("function() { return this.parentElement; }")
+    }
+
+    /// This element's child elements, in document order. Text nodes are skipped.
+    pub fn children(&self) -> Result<Vec<Element<'a>>> {
+        let array = self.call_js_fn(
+            "function() { return Array.from(this.children); }",
+            vec![],
+            false,
+        )?;
+        self.parent.elements_from_js_array(array)
+    }
+
+    /// The element following this one among its parent's children, if any.
+    pub fn next_sibling(&self) -> Result<Option<Element<'a>>> {
+        self.related_element("function() { return this.nextElementSibling; }")
+    }
+
+    /// The element preceding this one among its parent's children, if any.
+    pub fn previous_sibling(&self) -> Result<Option<Element<'a>>> {
+        self.related_element("function() { return this.previousElementSibling; }")
+    }
+
+    /// The closest ancestor of this element (starting with the element itself) which matches
+    /// `selector`, if any.
+    pub fn closest(&self, selector: &str) -> Result<Option<Element<'a>>> {
+        let object = self.call_js_fn(
+            "function(selector) { return this.closest(selector); }",
+            vec![selector.into()],
+            false,
+        )?;
+        self.parent.element_from_js_object(object)
+    }
+
+    /// The shadow root attached to this element, open or closed, if any.
+    ///
+    /// The returned `Element` stands for the shadow root itself, so `find_element` and friends
+    /// search inside the shadow tree.
+    pub fn shadow_root(&self) -> Result<Option<Element<'a>>> {
+        let node = self.describe_with_pierce()?;
+        node.shadow_roots
+            .and_then(|roots| roots.into_iter().next())
+            .map(|root| {
+                self.parent
+                    .element_from_backend_node_id(root.backend_node_id)
+            })
+            .transpose()
+    }
+
+    /// For an `<iframe>` (or `<frame>`), its document, so that `find_element` and friends
+    /// search inside the frame.
+    ///
+    /// This is `None` for other elements and for out-of-process (usually cross-origin) frames,
+    /// whose documents live in a separate target.
+    pub fn content_frame(&self) -> Result<Option<Element<'a>>> {
+        let node = self.describe_with_pierce()?;
+        node.content_document
+            .map(|document| {
+                self.parent
+                    .element_from_backend_node_id(document.backend_node_id)
+            })
+            .transpose()
+    }
+
+    /// Sets attribute `name` to `value`, adding the attribute if needed.
+    ///
+    /// `attributes` holds the attributes from when this `Element` was created and isn't
+    /// updated; use `get_attributes` to read the current ones.
+    pub fn set_attribute(&self, name: &str, value: &str) -> Result<&Self> {
+        debug!("Setting attribute {name}={value:?} on {:?}", &self);
+        self.parent.call_method(DOM::SetAttributeValue {
+            node_id: self.current_node_id()?,
+            name: name.to_string(),
+            value: value.to_string(),
+        })?;
+        Ok(self)
+    }
+
+    pub fn remove_attribute(&self, name: &str) -> Result<&Self> {
+        debug!("Removing attribute {name} from {:?}", &self);
+        self.parent.call_method(DOM::RemoveAttribute {
+            node_id: self.current_node_id()?,
+            name: name.to_string(),
+        })?;
+        Ok(self)
+    }
+
+    /// Replaces this element, including its tags, with `outer_html`. This `Element` no longer
+    /// refers to anything in the document afterwards.
+    pub fn set_outer_html(&self, outer_html: &str) -> Result<()> {
+        self.parent.call_method(DOM::SetOuterHTML {
+            node_id: self.current_node_id()?,
+            outer_html: outer_html.to_string(),
+        })?;
+        Ok(())
+    }
+
+    /// Removes this element from the document.
+    pub fn remove(&self) -> Result<()> {
+        debug!("Removing {:?}", &self);
+        self.parent.call_method(DOM::RemoveNode {
+            node_id: self.current_node_id()?,
+        })?;
+        Ok(())
+    }
+
+    /// Sets the value of a text, comment or attribute node (like the ones `find_element_by_xpath`
+    /// finds for `//p/text()`). Fails for elements.
+    pub fn set_node_value(&self, value: &str) -> Result<&Self> {
+        self.parent.call_method(DOM::SetNodeValue {
+            node_id: self.current_node_id()?,
+            value: value.to_string(),
+        })?;
+        Ok(self)
+    }
+
+    fn related_element(&self, function_declaration: &str) -> Result<Option<Element<'a>>> {
+        let object = self.call_js_fn(function_declaration, vec![], false)?;
+        self.parent.element_from_js_object(object)
+    }
+
+    fn describe_with_pierce(&self) -> Result<DOM::Node> {
+        Ok(self
+            .parent
+            .call_method(DOM::DescribeNode {
+                node_id: None,
+                backend_node_id: Some(self.backend_node_id),
+                object_id: None,
+                depth: Some(1),
+                pierce: Some(true),
+            })
+            .map_err(NoElementFound::map)?
+            .node)
+    }
+
+    /// `node_id` goes stale whenever the document is requested again (which e.g.
+    /// `Tab::find_element` does), so the `DOM` mutation methods, which only take node IDs, look
+    /// up a fresh one.
+    fn current_node_id(&self) -> Result<DOM::NodeId> {
+        Ok(self
+            .parent
+            .call_method(DOM::RequestNode {
+                object_id: self.remote_object_id.clone(),
+            })
+            .map_err(NoElementFound::map)?
+            .node_id)
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+
+use thiserror::Error;
+
+use log::debug;
+
+use serde_json::{Value as Json, json};
+
+use super::Element;
+
+#[derive(Debug, Error)]
+#[error("Can't fill form field: {reason}")]
+pub struct FormFieldError {
+    pub reason: String,
+}
+
+/// Returned by `Tab::fill_form` when some of the fields couldn't be filled, with the selector
+/// and error of each of them.
+#[derive(Debug, Error)]
+pub struct FillFormError {
+    pub failures: Vec<(String, anyhow::Error)>,
+}
+
+impl Display for FillFormError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "Couldn't fill {} form field(s):", self.failures.len())?;
+        for (selector, error) in &self.failures {
+            write!(f, " `{selector}`: {error};")?;
+        }
+        Ok(())
+    }
+}
+
+/// Which `<option>` of a `<select>` to pick, see `Element::select_option`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectOption {
+    Value(String),
+    /// The option's visible text
+    Label(String),
+    Index(usize),
+}
+
+/// A value for `Tab::fill_form`.
+///
+/// Text fills text fields and picks the `<option>` with that value (or else that label) in
+/// `<select>`s; booleans check or uncheck checkboxes and radio buttons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormValue {
+    Text(String),
+    Checked(bool),
+    Select(SelectOption),
+}
+
+impl From<&str> for FormValue {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for FormValue {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<bool> for FormValue {
+    fn from(checked: bool) -> Self {
+        Self::Checked(checked)
+    }
+}
+
+impl From<SelectOption> for FormValue {
+    fn from(option: SelectOption) -> Self {
+        Self::Select(option)
+    }
+}
+
+impl Element<'_> {
+    /// Replaces the value of an `<input>`, `<textarea>` or `[contenteditable]` element with
+    /// `value`, firing `input` and `change` events.
+    ///
+    /// Text is inserted the way an IME would, so there's a single `input` event rather than
+    /// one per key; use `type_into` if the page needs key events. Inputs like `date` or `color`
+    /// have their value set directly.
+    pub fn fill(&self, value: &str) -> Result<&Self> {
+        debug!("Filling element {:?} with {value:?}", &self);
+        let how = self.form_action(
+            "function(value) {
+                const settable = ['color', 'date', 'time', 'datetime-local', 'month', 'range', 'week'];
+                const typeable = ['', 'email', 'number', 'password', 'search', 'tel', 'text', 'url'];
+                if (this instanceof HTMLInputElement || this instanceof HTMLTextAreaElement) {
+                    const type = this instanceof HTMLInputElement ? this.type.toLowerCase() : '';
+                    if (!settable.includes(type) && !typeable.includes(type))
+                        return 'error:an input of type ' + type + ' cannot be filled';
+                    if (this.disabled)
+                        return 'error:element is disabled';
+                    if (this.readOnly)
+                        return 'error:element is read-only';
+                    this.focus();
+                    if (settable.includes(type)) {
+                        this.value = value;
+                        if (this.value !== value)
+                            return 'error:malformed value for an input of type ' + type;
+                        this.dispatchEvent(new Event('input', { bubbles: true }));
+                        this.dispatchEvent(new Event('change', { bubbles: true }));
+                        return 'done';
+                    }
+                    if (type === 'number' && value.trim() !== '' && isNaN(Number(value)))
+                        return 'error:cannot type text into an input of type number';
+                    this.select();
+                    return 'insert';
+                }
+                if (this.isContentEditable) {
+                    this.focus();
+                    const range = document.createRange();
+                    range.selectNodeContents(this);
+                    const selection = window.getSelection();
+                    selection.removeAllRanges();
+                    selection.addRange(range);
+                    return 'insert';
+                }
+                return 'error:element is not an <input>, <textarea> or [contenteditable] element';
+            }",
+            vec![json!(value)],
+        )?;
+
+        if how == "insert" {
+            if value.is_empty() {
+                self.parent.press_key("Delete")?;
+            } else {
+                self.parent.send_character(value)?;
+            }
+            self.call_js_fn(
+                "function() {
+                    if (!this.isContentEditable)
+                        this.dispatchEvent(new Event('change', { bubbles: true }));
+                }",
+                vec![],
+                false,
+            )?;
+        }
+        Ok(self)
+    }
+
+    /// Empties an `<input>`, `<textarea>` or `[contenteditable]` element; see `fill`.
+    pub fn clear(&self) -> Result<&Self> {
+        self.fill("")
+    }
+
+    /// Selects an `<option>` of this `<select>` element, firing `input` and `change` events.
+    ///
+    /// For a `<select multiple>`, this replaces the current selection.
+    pub fn select_option(&self, option: &SelectOption) -> Result<&Self> {
+        debug!("Selecting option {option:?} of {:?}", &self);
+        let (by, wanted) = match option {
+            SelectOption::Value(value) => ("value", json!(value)),
+            SelectOption::Label(label) => ("label", json!(label)),
+            SelectOption::Index(index) => ("index", json!(index)),
+        };
+        self.form_action(
+            "function(by, wanted) {
+                if (!(this instanceof HTMLSelectElement))
+                    return 'error:element is not a <select> element';
+                if (this.disabled)
+                    return 'error:element is disabled';
+                const options = Array.from(this.options);
+                const option = by === 'index'
+                    ? options[wanted]
+                    : options.find(o => by === 'value'
+                        ? o.value === wanted
+                        : o.label === wanted || o.textContent.trim() === wanted);
+                if (!option)
+                    return 'error:no option with ' + by + ' ' + JSON.stringify(wanted);
+                if (option.disabled)
+                    return 'error:option is disabled';
+                if (this.multiple)
+                    options.forEach(o => o.selected = false);
+                option.selected = true;
+                this.dispatchEvent(new Event('input', { bubbles: true }));
+                this.dispatchEvent(new Event('change', { bubbles: true }));
+                return 'done';
+            }",
+            vec![json!(by), wanted],
+        )?;
+        Ok(self)
+    }
+
+    /// Checks this checkbox or radio button by clicking it, unless it's already checked.
+    pub fn check(&self) -> Result<&Self> {
+        self.set_checked(true)
+    }
+
+    /// Unchecks this checkbox by clicking it, unless it's already unchecked.
+    pub fn uncheck(&self) -> Result<&Self> {
+        self.set_checked(false)
+    }
+
+    /// Clicks this checkbox or radio button if its state isn't `checked` yet, and makes sure
+    /// the click had the desired effect.
+    pub fn set_checked(&self, checked: bool) -> Result<&Self> {
+        let state = self.form_action(
+            "function(checked) {
+                const isToggle = this instanceof HTMLInputElement
+                    && (this.type === 'checkbox' || this.type === 'radio');
+                if (!isToggle && !['checkbox', 'radio'].includes(this.getAttribute('role')))
+                    return 'error:element is not a checkbox or radio button';
+                if (this.type === 'radio' && !checked && this.checked)
+                    return 'error:a radio button cannot be unchecked';
+                const current = isToggle ? this.checked : this.getAttribute('aria-checked') === 'true';
+                return current === checked ? 'done' : 'click';
+            }",
+            vec![json!(checked)],
+        )?;
+        if state == "done" {
+            return Ok(self);
+        }
+
+        self.click()?;
+
+        let now_checked = self
+            .call_js_fn(
+                "function() {
+                    return this instanceof HTMLInputElement
+                        ? this.checked
+                        : this.getAttribute('aria-checked') === 'true';
+                }",
+                vec![],
+                false,
+            )?
+            .value
+            .and_then(|v| v.as_bool());
+        if now_checked != Some(checked) {
+            return Err(FormFieldError {
+                reason: "clicking the element didn't change its state".to_string(),
+            }
+            .into());
+        }
+        Ok(self)
+    }
+
+    /// Picks whichever of `fill`, `select_option` and `set_checked` suits this element.
+    pub(crate) fn fill_value(&self, value: &FormValue) -> Result<&Self> {
+        match value {
+            FormValue::Checked(checked) => self.set_checked(*checked),
+            FormValue::Select(option) => self.select_option(option),
+            FormValue::Text(text) if self.tag_name == "SELECT" => self
+                .select_option(&SelectOption::Value(text.clone()))
+                .or_else(|_| self.select_option(&SelectOption::Label(text.clone()))),
+            FormValue::Text(text) => self.fill(text),
+        }
+    }
+
+    /// Runs a form helper's JS, which returns a string describing what to do next or
+    /// `error:<reason>`.
+    fn form_action(&self, function_declaration: &str, args: Vec<Json>) -> Result<String> {
+        let result = self
+            .call_js_fn(function_declaration, args, false)?
+            .value
+            .and_then(|v| v.as_str().map(ToString::to_string))
+            .unwrap_or_default();
+        match result.strip_prefix("error:") {
+            Some(reason) => Err(FormFieldError {
+                reason: reason.to_string(),
+            }
+            .into()),
+            None => Ok(result),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use log::debug;
+
+use crate::browser::tab::{NoElementFound, Tab};
+use crate::protocol::cdp::{DOM, Runtime};
+
+use super::Element;
+
+/// An owned handle to a [DOM Element](https://developer.mozilla.org/en-US/docs/Web/API/Element).
+///
+/// Unlike `Element`, which borrows its `Tab`, an `ElementHandle` keeps an `Arc<Tab>`, so it can
+/// be stored in structs, returned from functions or sent to other threads.
+///
+/// The full `Element` API is available through [`ElementHandle::element`]. The handle owns its
+/// own remote object in the page, which is released via `Runtime.releaseObject` when the handle
+/// is dropped.
+///
+/// ```rust,no_run
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// #
+/// use headless_chrome::Browser;
+/// let browser = Browser::default()?;
+/// let tab = browser.new_tab()?;
+/// tab.navigate_to("https://www.wikipedia.org")?.wait_until_navigated()?;
+///
+/// let search_box = tab.find_element_handle("input#searchInput")?;
+/// std::thread::spawn(move || -> Result<()> {
+///     search_box.element()?.type_into("WebKit")?;
+///     Ok(())
+/// })
+/// .join()
+/// .unwrap()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct ElementHandle {
+    pub remote_object_id: String,
+    pub backend_node_id: DOM::BackendNodeId,
+    pub tab: Arc<Tab>,
+    pub attributes: Option<Vec<String>>,
+    pub tag_name: String,
+    pub value: String,
+}
+
+impl Debug for ElementHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "ElementHandle {}", self.backend_node_id)?;
+        Ok(())
+    }
+}
+
+impl ElementHandle {
+    /// Creates a handle to the node identified by `backend_node_id`, resolving a new remote
+    /// object for it which the handle owns.
+    pub fn new(tab: Arc<Tab>, backend_node_id: DOM::BackendNodeId) -> Result<Self> {
+        let node = tab
+            .call_method(DOM::DescribeNode {
+                node_id: None,
+                backend_node_id: Some(backend_node_id),
+                depth: Some(0),
+                object_id: None,
+                pierce: None,
+            })
+            .map_err(NoElementFound::map)?
+            .node;
+
+        let object = tab
+            .call_method(DOM::ResolveNode {
+                backend_node_id: Some(backend_node_id),
+                node_id: None,
+                object_group: None,
+                execution_context_id: None,
+            })
+            .map_err(NoElementFound::map)?
+            .object;
+
+        let value = object.value.unwrap_or("".into()).to_string();
+        let remote_object_id = object.object_id.expect("couldn't find object ID");
+
+        Ok(Self {
+            remote_object_id,
+            backend_node_id,
+            tab,
+            attributes: node.attributes,
+            tag_name: node.node_name,
+            value,
+        })
+    }
+
+    /// Borrows this handle as an `Element`, giving access to the full `Element` API.
+    ///
+    /// The element's `node_id` is looked up afresh, so this keeps working after node IDs have
+    /// been invalidated (e.g. by `Tab::find_element` requesting the document again).
+    pub fn element(&self) -> Result<Element<'_>> {
+        let node_id = self
+            .tab
+            .call_method(DOM::RequestNode {
+                object_id: self.remote_object_id.clone(),
+            })
+            .map_err(NoElementFound::map)?
+            .node_id;
+
+        Ok(Element {
+            remote_object_id: self.remote_object_id.clone(),
+            backend_node_id: self.backend_node_id,
+            node_id,
+            parent: &self.tab,
+            attributes: self.attributes.clone(),
+            tag_name: self.tag_name.clone(),
+            value: self.value.clone(),
+        })
+    }
+}
+
+impl Drop for ElementHandle {
+    fn drop(&mut self) {
+        if let Err(error) = self.tab.call_method(Runtime::ReleaseObject {
+            object_id: self.remote_object_id.clone(),
+        }) {
+            debug!("Failed to release element handle's remote object: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ElementHandle;
+
+    fn is_send_and_sync<T>()
+    where
+        T: Send + Sync,
+    {
+    }
+
+    #[test]
+    fn test_if_element_handle_is_send_and_sync() {
+        is_send_and_sync::<ElementHandle>();
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Error, Result};
@@ -7,19 +8,55 @@ use thiserror::Error;
 
 use log::debug;
 
-use crate::browser::tab::NoElementFound;
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+
+use crate::browser::tab::js;
+use crate::browser::tab::locator::ElementNotActionable;
+use crate::browser::tab::{ClickOptions, ModifierKey, NoElementFound, SwipeDirection};
 use crate::{browser::tab::point::Point, protocol::cdp::CSS::CSSComputedStyleProperty};
 
 mod box_model;
+mod dom;
+mod form;
+mod handle;
 
 use crate::util;
-pub use box_model::{BoxModel, ElementQuad};
+pub use box_model::{BoundingBox, BoxModel, ElementQuad};
+pub use form::{FillFormError, FormFieldError, FormValue, SelectOption};
+pub use handle::ElementHandle;
 
-use crate::protocol::cdp::{CSS, DOM, Page, Runtime};
+use crate::protocol::cdp::{CSS, DOM, Input::MouseButton, Page, Runtime};
 
 #[derive(Debug, Error)]
 #[error("Couldnt get element quad")]
 pub struct NoQuadFound {}
+
+/// Which conditions an element has to meet before it's acted upon, see
+/// `Element::check_actionable`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Checks {
+    pub visible: bool,
+    pub stable: bool,
+    pub enabled: bool,
+}
+
+impl Checks {
+    pub const ATTACHED: Self = Self {
+        visible: false,
+        stable: false,
+        enabled: false,
+    };
+    pub const ALL: Self = Self {
+        visible: true,
+        stable: true,
+        enabled: true,
+    };
+}
+
+/// Number of intermediate mouse moves `Element::drag_to` dispatches.
+const DRAG_STEPS: u32 = 10;
+
 /// A handle to a [DOM Element](https://developer.mozilla.org/en-US/docs/Web/API/Element).
 ///
 /// Typically you get access to these by passing `Tab.wait_for_element` a CSS selector. Once
@@ -43,6 +80,13 @@ impl Debug for Element<'_> {
     }
 }
 
+impl Serialize for Element<'_> {
+    /// Serializes to a reference to the DOM node, for use as an argument of `call_function`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        js::serialize_object_id(&self.remote_object_id, serializer)
+    }
+}
+
 impl<'a> Element<'a> {
     /// Using a 'node_id', of the type returned by QuerySelector and QuerySelectorAll, this finds
     /// the 'backend_node_id' and 'remote_object_id' which are stable identifiers, unlike node_id.
@@ -82,6 +126,18 @@ impl<'a> Element<'a> {
         })
     }
 
+    /// Creates an owned `ElementHandle` to this element, which isn't tied to the lifetime of the
+    /// borrowed `Tab`. `tab` must be the tab this element belongs to.
+    pub fn to_handle(&self, tab: &Arc<super::Tab>) -> Result<ElementHandle> {
+        if !std::ptr::eq(Arc::as_ptr(tab), self.parent) {
+            return Err(anyhow::anyhow!(
+                "Element {} does not belong to the given tab",
+                self.backend_node_id
+            ));
+        }
+        ElementHandle::new(Arc::clone(tab), self.backend_node_id)
+    }
+
     /// Returns the first element in the document which matches the given CSS selector.
     ///
     /// Equivalent to the following JS:
@@ -207,6 +263,62 @@ impl<'a> Element<'a> {
             })
     }
 
+    /// Like `find_element`, but also looks inside open shadow roots, including this element's
+    /// own. See `Tab::find_element_deep` for the selector syntax.
+    pub fn find_element_deep(&self, selector: &str) -> Result<Element<'a>> {
+        self.find_elements_deep(selector)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| NoElementFound {}.into())
+    }
+
+    /// Like `find_elements`, but also looks inside open shadow roots, including this element's
+    /// own. See `Tab::find_element_deep` for the selector syntax.
+    ///
+    /// Like `Tab::find_elements`, fails with `NoElementFound` if nothing matches.
+    pub fn find_elements_deep(&self, selector: &str) -> Result<Vec<Element<'a>>> {
+        let selectors: Vec<&str> = selector.split(">>>").map(str::trim).collect();
+        if selectors.iter().any(|s| s.is_empty()) {
+            return Err(anyhow::anyhow!("Invalid deep selector: {selector:?}"));
+        }
+
+        let array = self.call_js_fn(
+            "function(selectors) {
+                const deepQuerySelectorAll = (root, selector) => {
+                    const found = [];
+                    const visit = scope => {
+                        found.push(...scope.querySelectorAll(selector));
+                        for (const element of scope.querySelectorAll('*'))
+                            if (element.shadowRoot)
+                                visit(element.shadowRoot);
+                    };
+                    visit(root);
+                    if (root.shadowRoot)
+                        visit(root.shadowRoot);
+                    return found;
+                };
+
+                let roots = [this];
+                for (const selector of selectors) {
+                    const next = new Set();
+                    for (const root of roots)
+                        for (const element of deepQuerySelectorAll(root, selector))
+                            next.add(element);
+                    roots = [...next];
+                }
+                return roots;
+            }",
+            vec![serde_json::json!(selectors)],
+            false,
+        )?;
+
+        let elements = self.parent.elements_from_js_array(array)?;
+        if elements.is_empty() {
+            return Err(NoElementFound {}.into());
+        }
+        Ok(elements)
+    }
+
     pub fn wait_for_element(&self, selector: &str) -> Result<Element<'_>> {
         self.wait_for_element_with_custom_timeout(selector, Duration::from_secs(3))
     }
@@ -258,16 +370,262 @@ impl<'a> Element<'a> {
     /// Moves the mouse to the middle of this element
     pub fn move_mouse_over(&self) -> Result<&Self> {
         self.scroll_into_view()?;
-        let midpoint = self.get_midpoint()?;
+        let midpoint = self.get_content_midpoint()?;
         self.parent.move_mouse_to_point(midpoint)?;
         Ok(self)
     }
 
+    /// Scrolls this element into view and clicks its midpoint.
+    ///
+    /// Before clicking, this makes sure that the element is visible, enabled and not moving
+    /// any more, and that it (rather than something covering it) would receive the click.
+    /// Otherwise, this fails with an `ElementNotActionable` error explaining why.
     pub fn click(&self) -> Result<&Self> {
+        self.click_with_options(&ClickOptions::default())
+    }
+
+    pub fn double_click(&self) -> Result<&Self> {
+        self.click_with_options(&ClickOptions {
+            click_count: 2,
+            ..Default::default()
+        })
+    }
+
+    pub fn right_click(&self) -> Result<&Self> {
+        self.click_with_options(&ClickOptions {
+            button: MouseButton::Right,
+            ..Default::default()
+        })
+    }
+
+    pub fn middle_click(&self) -> Result<&Self> {
+        self.click_with_options(&ClickOptions {
+            button: MouseButton::Middle,
+            ..Default::default()
+        })
+    }
+
+    /// Clicks this element while holding down the given modifier keys, e.g. `Ctrl` (or `Meta` on
+    /// macOS) to open a link in a new tab, or `Shift` to extend a selection.
+    pub fn click_with_modifiers(&self, modifiers: &[ModifierKey]) -> Result<&Self> {
+        self.click_with_options(&ClickOptions {
+            modifiers: modifiers.to_vec(),
+            ..Default::default()
+        })
+    }
+
+    /// Scrolls this element into view and clicks it as described by `options`, with the same
+    /// checks as `click`.
+    ///
+    /// If `options.offset` is given, it's relative to the top-left corner of the element's
+    /// border box; otherwise the element's midpoint is clicked.
+    pub fn click_with_options(&self, options: &ClickOptions) -> Result<&Self> {
+        self.scroll_into_view()?;
+        debug!("Clicking element {:?} with {:?}", &self, options);
+        self.check_actionable(Checks::ALL)?;
+        let point = match options.offset {
+            Some(offset) => self.get_box_model()?.border.top_left + offset,
+            None => self.get_content_midpoint()?,
+        };
+        self.check_hit_target(point)?;
+        self.parent.click_point_with_options(point, options)?;
+        Ok(self)
+    }
+
+    /// Fails with `ElementNotActionable` unless this element meets `checks`.
+    pub(crate) fn check_actionable(&self, checks: Checks) -> Result<()> {
+        let result = self.call_js_fn(
+            "async function(visible, stable, enabled) {
+                if (!this.isConnected)
+                    return 'element is detached from the document';
+                if (visible && !this.checkVisibility())
+                    return 'element is not visible';
+                if (enabled && (this.matches(':disabled') || this.closest('[aria-disabled=true]')))
+                    return 'element is disabled';
+                if (stable) {
+                    const rect = () => {
+                        const r = this.getBoundingClientRect();
+                        return [r.x, r.y, r.width, r.height].join();
+                    };
+                    const before = rect();
+                    // background tabs may not run animation frames at all
+                    await new Promise(resolve => {
+                        requestAnimationFrame(() => requestAnimationFrame(resolve));
+                        setTimeout(resolve, 100);
+                    });
+                    if (before !== rect())
+                        return 'element is not stable';
+                }
+                return true;
+            }",
+            vec![
+                checks.visible.into(),
+                checks.stable.into(),
+                checks.enabled.into(),
+            ],
+            true,
+        )?;
+
+        match result.value {
+            Some(serde_json::Value::Bool(true)) => Ok(()),
+            Some(serde_json::Value::String(reason)) => Err(ElementNotActionable { reason }.into()),
+            other => Err(ElementNotActionable {
+                reason: format!("unexpected actionability result: {other:?}"),
+            }
+            .into()),
+        }
+    }
+
+    /// The midpoint of the element's first content quad, without `get_midpoint`'s JS fallback.
+    pub(crate) fn get_content_midpoint(&self) -> Result<Point> {
+        self.parent
+            .call_method(DOM::GetContentQuads {
+                node_id: None,
+                backend_node_id: Some(self.backend_node_id),
+                object_id: None,
+            })
+            .map_err(NoElementFound::map)?
+            .quads
+            .first()
+            .map(|raw_quad| {
+                let quad = ElementQuad::from_raw_points(raw_quad);
+                (quad.bottom_right + quad.top_left) / 2.0
+            })
+            .ok_or_else(|| {
+                ElementNotActionable {
+                    reason: "element is not rendered".to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Fails with `ElementNotActionable`, naming the culprit, if a click at `point` would land on
+    /// something other than this element or its descendants.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn check_hit_target(&self, point: Point) -> Result<()> {
+        let hit = self.parent.call_method(DOM::GetNodeForLocation {
+            x: point.x.max(0.0) as u32,
+            y: point.y.max(0.0) as u32,
+            include_user_agent_shadow_dom: Some(false),
+            ignore_pointer_events_none: None,
+        })?;
+        if hit.backend_node_id == self.backend_node_id {
+            return Ok(());
+        }
+
+        let Some(hit_object_id) = self
+            .parent
+            .call_method(DOM::ResolveNode {
+                node_id: None,
+                backend_node_id: Some(hit.backend_node_id),
+                object_group: None,
+                execution_context_id: None,
+            })?
+            .object
+            .object_id
+        else {
+            return Ok(());
+        };
+
+        let covering = self.parent.call_method(Runtime::CallFunctionOn {
+            object_id: Some(self.remote_object_id.clone()),
+            function_declaration: "function(hit) {
+                for (let node = hit; node; node = node.parentNode || node.host) {
+                    if (node === this)
+                        return null;
+                }
+                let description = hit.nodeName.toLowerCase();
+                if (hit.id)
+                    description += '#' + hit.id;
+                for (const name of hit.classList || [])
+                    description += '.' + name;
+                return description;
+            }"
+            .to_string(),
+            arguments: Some(vec![Runtime::CallArgument {
+                value: None,
+                unserializable_value: None,
+                object_id: Some(hit_object_id.clone()),
+            }]),
+            return_by_value: Some(true),
+            generate_preview: None,
+            silent: Some(true),
+            await_promise: None,
+            user_gesture: None,
+            execution_context_id: None,
+            object_group: None,
+            throw_on_side_effect: None,
+            serialization_options: None,
+            unique_context_id: None,
+        });
+        if let Err(error) = self.parent.call_method(Runtime::ReleaseObject {
+            object_id: hit_object_id,
+        }) {
+            debug!("Failed to release hit test object: {error}");
+        }
+
+        match covering?.result.value {
+            Some(serde_json::Value::String(covering)) => Err(ElementNotActionable {
+                reason: format!(
+                    "{covering} would receive the click at ({}, {}) instead",
+                    point.x, point.y
+                ),
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Taps this element with one finger, enabling touch emulation if needed.
+    pub fn tap(&self) -> Result<&Self> {
+        self.scroll_into_view()?;
+        debug!("Tapping element {:?}", &self);
+        let midpoint = self.get_content_midpoint()?;
+        self.parent.tap_point(midpoint)?;
+        Ok(self)
+    }
+
+    /// Touches this element and holds the finger down; see `Tab::long_press_point`.
+    pub fn long_press(&self) -> Result<&Self> {
+        self.scroll_into_view()?;
+        let midpoint = self.get_content_midpoint()?;
+        self.parent.long_press_point(midpoint)?;
+        Ok(self)
+    }
+
+    /// Swipes across this element, starting at its midpoint; see `Tab::swipe_from_point`.
+    pub fn swipe(&self, direction: SwipeDirection, distance: f64) -> Result<&Self> {
         self.scroll_into_view()?;
-        debug!("Clicking element {:?}", &self);
-        let midpoint = self.get_midpoint()?;
-        self.parent.click_point(midpoint)?;
+        let midpoint = self.get_content_midpoint()?;
+        self.parent
+            .swipe_from_point(midpoint, direction, distance)?;
+        Ok(self)
+    }
+
+    /// Pinches this element with two fingers; see `Tab::pinch_at_point`.
+    pub fn pinch(&self, scale: f64) -> Result<&Self> {
+        self.scroll_into_view()?;
+        let midpoint = self.get_content_midpoint()?;
+        self.parent.pinch_at_point(midpoint, scale)?;
+        Ok(self)
+    }
+
+    /// Drags this element onto `target` with the left mouse button; see `Tab::drag`.
+    pub fn drag_to(&self, target: &Element<'_>) -> Result<&Self> {
+        self.scroll_into_view()?;
+        debug!("Dragging element {:?} to {:?}", &self, target);
+        let from = self.get_content_midpoint()?;
+        let to = target.get_content_midpoint()?;
+        self.parent.drag(from, to, DRAG_STEPS)?;
+        Ok(self)
+    }
+
+    /// Drops files from disk onto this element, e.g. an upload drop zone.
+    /// The paths should be absolute.
+    pub fn drop_files(&self, file_paths: &[&str]) -> Result<&Self> {
+        self.scroll_into_view()?;
+        let midpoint = self.get_content_midpoint()?;
+        self.parent.drop_files_at_point(midpoint, file_paths)?;
         Ok(self)
     }
 
@@ -483,6 +841,17 @@ impl<'a> Element<'a> {
         Ok(self)
     }
 
+    /// Scrolls this element's own content by `delta_x`/`delta_y` CSS pixels, for elements that
+    /// are scroll containers themselves (e.g. a list with `overflow: auto`).
+    pub fn scroll_within(&self, delta_x: f64, delta_y: f64) -> Result<&Self> {
+        self.call_js_fn(
+            "function(left, top) { this.scrollBy({ left, top, behavior: 'instant' }); }",
+            vec![delta_x.into(), delta_y.into()],
+            false,
+        )?;
+        Ok(self)
+    }
+
     pub fn get_attributes(&self) -> Result<Option<Vec<String>>> {
         let description = self.get_description()?;
         Ok(description.attributes)
@@ -609,6 +978,156 @@ impl<'a> Element<'a> {
 
         Ok(p)
     }
+
+    /// Whether this element isn't disabled, be it by its own `disabled` attribute, a disabled
+    /// `<fieldset>` or `aria-disabled`.
+    pub fn is_enabled(&self) -> Result<bool> {
+        self.call_function(
+            "function() {
+                return !(this.matches(':disabled') || this.closest('[aria-disabled=true]'));
+            }",
+            (),
+        )
+    }
+
+    /// Whether this checkbox or radio button (native or ARIA) is checked. Fails for other
+    /// elements.
+    pub fn is_checked(&self) -> Result<bool> {
+        self.call_function(
+            "function() {
+                if (this instanceof HTMLInputElement && ['checkbox', 'radio'].includes(this.type))
+                    return this.checked;
+                const role = this.getAttribute('role');
+                if (['checkbox', 'radio', 'switch', 'menuitemcheckbox', 'menuitemradio'].includes(role))
+                    return this.getAttribute('aria-checked') === 'true';
+                throw new Error('Not a checkbox or radio button');
+            }",
+            (),
+        )
+    }
+
+    /// Whether the user could change this element's value: an enabled and not read-only
+    /// `<input>`, `<textarea>` or `<select>`, or a `[contenteditable]` element. Fails for other
+    /// elements.
+    pub fn is_editable(&self) -> Result<bool> {
+        self.call_function(
+            "function() {
+                if (this.isContentEditable)
+                    return true;
+                const isFormField = this instanceof HTMLInputElement
+                    || this instanceof HTMLTextAreaElement
+                    || this instanceof HTMLSelectElement;
+                if (!isFormField)
+                    throw new Error('Not an <input>, <textarea>, <select> or [contenteditable] element');
+                return !this.matches(':disabled')
+                    && !this.readOnly
+                    && this.getAttribute('aria-readonly') !== 'true';
+            }",
+            (),
+        )
+    }
+
+    /// Whether this element has focus (in its document or shadow root).
+    pub fn is_focused(&self) -> Result<bool> {
+        self.call_function(
+            "function() { return this.getRootNode().activeElement === this; }",
+            (),
+        )
+    }
+
+    /// Whether at least `ratio` (between 0.0 and 1.0) of this element is inside the viewport.
+    /// An element with a `ratio` of 0.0 has to be at least partially visible.
+    pub fn is_in_viewport(&self, ratio: f64) -> Result<bool> {
+        self.call_function(
+            "function(ratio) {
+                // computed directly rather than with an IntersectionObserver, whose callback
+                // only runs on rendering updates, which background tabs may never get
+                const rect = this.getBoundingClientRect();
+                const width = Math.min(rect.right, window.innerWidth) - Math.max(rect.left, 0);
+                const height = Math.min(rect.bottom, window.innerHeight) - Math.max(rect.top, 0);
+                if (width < 0 || height < 0)
+                    return false;
+                const area = rect.width * rect.height;
+                const visibleRatio = area > 0 ? (width * height) / area : 1;
+                return visibleRatio > 0 && visibleRatio >= ratio;
+            }",
+            (ratio,),
+        )
+    }
+
+    /// The current value of this `<input>`, `<textarea>` or `<select>` element, which (unlike
+    /// `value`) follows whatever has been typed since the element was found.
+    pub fn input_value(&self) -> Result<String> {
+        self.call_function(
+            "function() {
+                const isFormField = this instanceof HTMLInputElement
+                    || this instanceof HTMLTextAreaElement
+                    || this instanceof HTMLSelectElement;
+                if (!isFormField)
+                    throw new Error('Not an <input>, <textarea> or <select> element');
+                return this.value;
+            }",
+            (),
+        )
+    }
+
+    /// This element's border box in CSS pixels relative to the viewport, or `None` if it isn't
+    /// rendered (e.g. `display: none`).
+    pub fn bounding_box(&self) -> Result<Option<BoundingBox>> {
+        self.call_function(
+            "function() {
+                if (this.getClientRects().length === 0)
+                    return null;
+                const rect = this.getBoundingClientRect();
+                return { x: rect.x, y: rect.y, width: rect.width, height: rect.height };
+            }",
+            (),
+        )
+    }
+
+    /// Reads the JS property `name` of this element (e.g. `checked`, `scrollTop`, `dataset`)
+    /// and deserializes it. A missing property is `null`, so use an `Option` if it may not be
+    /// there.
+    pub fn get_property<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        self.call_function("function(name) { return this[name]; }", (name,))
+    }
+
+    /// Calls `function_declaration` with `this` bound to this element, awaiting any promise it
+    /// returns, and deserializes the result into `R`.
+    ///
+    /// `args` is a tuple of positional arguments (see `CallArgs`), e.g. `()` for none or
+    /// `(value,)` for one, each serialized to JSON. `Element`s and `JsHandle`s in the tuple are
+    /// passed by reference, so the function receives the DOM node itself. Exceptions thrown by
+    /// the function become a `JsException`.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// let body = tab.navigate_to("https://example.com")?.wait_for_element("body")?;
+    /// let heading = tab.find_element("h1")?;
+    /// let contains: bool = body.call_function("function(other) { return this.contains(other); }", (&heading,))?;
+    /// assert!(contains);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_function<A: js::CallArgs, R: DeserializeOwned>(
+        &self,
+        function_declaration: &str,
+        args: A,
+    ) -> Result<R> {
+        js::call_function_on(
+            self.parent,
+            self.remote_object_id.clone(),
+            function_declaration,
+            args,
+        )
+        .map_err(NoElementFound::map)
+    }
 }
 
 #[derive(Debug, Error)]
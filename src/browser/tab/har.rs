@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+use base64::Engine;
+
+use serde::{Deserialize, Serialize};
+
+use super::network::UrlMatcher;
+use super::route::Route;
+use crate::protocol::cdp::Network::{
+    self, GetResponseBodyReturnObject, RequestId, events::RequestWillBeSentEventParams,
+};
+use crate::protocol::cdp::types::Event;
+
+/// An [HTTP Archive](http://www.softwareishard.com/blog/har-12-spec/) (HAR 1.2) of the
+/// requests made by a tab, as returned by `Tab::stop_har_recording`.
+///
+/// Besides the standard fields, entries have Chrome's `_resourceType`, `_error` and
+/// `_transferSize` extensions. Deserialization is lenient, so HAR files written by browsers
+/// and other tools can be loaded as well.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarEntry {
+    /// ISO 8601, e.g. `2024-05-01T12:00:00.123Z`
+    pub started_date_time: String,
+    /// Total time of the request in milliseconds
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub cache: serde_json::Map<String, serde_json::Value>,
+    pub timings: HarTimings,
+    #[serde(rename = "serverIPAddress", skip_serializing_if = "Option::is_none")]
+    pub server_ip_address: Option<String>,
+    #[serde(rename = "_resourceType", skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    /// Why the request failed, if it did
+    #[serde(rename = "_error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub cookies: Vec<HarCookie>,
+    pub headers: Vec<HarHeader>,
+    pub query_string: Vec<HarQueryParam>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+    /// -1 if unknown
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarResponse {
+    /// 0 for failed requests
+    pub status: u32,
+    pub status_text: String,
+    pub http_version: String,
+    pub cookies: Vec<HarCookie>,
+    pub headers: Vec<HarHeader>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    /// -1 if unknown
+    pub headers_size: i64,
+    /// -1 if unknown
+    pub body_size: i64,
+    /// Bytes received for this response, headers included
+    #[serde(rename = "_transferSize", skip_serializing_if = "Option::is_none")]
+    pub transfer_size: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarContent {
+    /// Size of the decoded body
+    pub size: i64,
+    pub mime_type: String,
+    /// Only recorded with `HarRecordingOptions::capture_content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// `base64` for binary bodies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarCookie {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarQueryParam {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarPostData {
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// Durations of the phases of a request in milliseconds, -1 for phases which didn't happen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarTimings {
+    pub blocked: f64,
+    pub dns: f64,
+    /// Includes `ssl`
+    pub connect: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+    pub ssl: f64,
+}
+
+impl Default for HarTimings {
+    fn default() -> Self {
+        Self {
+            blocked: -1.0,
+            dns: -1.0,
+            connect: -1.0,
+            send: 0.0,
+            wait: 0.0,
+            receive: 0.0,
+            ssl: -1.0,
+        }
+    }
+}
+
+impl Har {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+/// Options for `Tab::start_har_recording_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct HarRecordingOptions {
+    /// Whether to fetch and store response bodies, which is slow and makes archives large
+    pub capture_content: bool,
+}
+
+/// What `Tab::route_from_har` does with requests which aren't in the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HarNotFound {
+    /// Fail them with `net::ERR_FAILED`, to make sure nothing reaches the network
+    #[default]
+    Abort,
+    /// Leave them to other routes, or else the network
+    Fallback,
+}
+
+/// Options for `Tab::route_from_har`.
+#[derive(Debug, Clone)]
+pub struct HarReplayOptions {
+    /// Only requests matching this are looked up in the archive; all requests by default
+    pub url: Option<UrlMatcher>,
+    /// Whether an entry's method has to be the request's
+    pub match_method: bool,
+    /// Whether an entry's post data has to be the request's
+    pub match_body: bool,
+    /// Whether to compare URLs without their query strings
+    pub ignore_query: bool,
+    pub not_found: HarNotFound,
+    /// Let requests which aren't in the archive through to the network, and add them to the
+    /// archive file when the route is removed with `Tab::unroute` or `Tab::unroute_all`, or
+    /// when `Tab::save_har_updates` is called. A missing archive file is created. Requests
+    /// which haven't been saved by the time the tab is dropped are lost.
+    pub update: bool,
+}
+
+impl Default for HarReplayOptions {
+    fn default() -> Self {
+        Self {
+            url: None,
+            match_method: true,
+            match_body: true,
+            ignore_query: false,
+            not_found: HarNotFound::default(),
+            update: false,
+        }
+    }
+}
+
+impl HarReplayOptions {
+    /// Whether `entry` is a recording of a request to `url` with `method` and `post_data`.
+    fn matches(&self, entry: &HarEntry, url: &str, method: &str, post_data: Option<&str>) -> bool {
+        let without_query = |url: &str| {
+            url.split_once('?')
+                .map_or(url, |(path, _)| path)
+                .to_string()
+        };
+        let urls_match = if self.ignore_query {
+            without_query(&entry.request.url) == without_query(url)
+        } else {
+            entry.request.url == url
+        };
+        let entry_post_data = entry
+            .request
+            .post_data
+            .as_ref()
+            .map(|data| data.text.as_str());
+        urls_match
+            && (!self.match_method || entry.request.method.eq_ignore_ascii_case(method))
+            && (!self.match_body || entry_post_data.unwrap_or("") == post_data.unwrap_or(""))
+    }
+
+    /// The entry to answer a request with, preferring ones which didn't fail.
+    pub(crate) fn find_entry<'a>(
+        &self,
+        har: &'a Har,
+        request: &Network::Request,
+    ) -> Option<&'a HarEntry> {
+        let mut candidates = har.log.entries.iter().filter(|entry| {
+            self.matches(
+                entry,
+                &request.url,
+                &request.method,
+                request.post_data.as_deref(),
+            )
+        });
+        let first = candidates.next()?;
+        if first.error.is_none() {
+            return Some(first);
+        }
+        candidates
+            .find(|entry| entry.error.is_none())
+            .or(Some(first))
+    }
+
+    /// Adds the entries of `recorded` which aren't in `archive` yet to it, for update mode.
+    pub(crate) fn add_new_entries(&self, archive: &mut Har, recorded: Har) {
+        if archive.log.version.is_empty() {
+            archive.log.version = recorded.log.version;
+            archive.log.creator = recorded.log.creator;
+        }
+        for entry in recorded.log.entries {
+            let wanted = self
+                .url
+                .as_ref()
+                .is_none_or(|matcher| matcher.matches(&entry.request.url));
+            let post_data = entry
+                .request
+                .post_data
+                .as_ref()
+                .map(|data| data.text.as_str());
+            let archived = archive.log.entries.iter().any(|archived| {
+                self.matches(
+                    archived,
+                    &entry.request.url,
+                    &entry.request.method,
+                    post_data,
+                )
+            });
+            if wanted && !archived {
+                archive.log.entries.push(entry);
+            }
+        }
+    }
+}
+
+/// Answers `route` with the response recorded in `entry`.
+pub(crate) fn fulfill_from_entry(route: &Route, entry: &HarEntry) -> Result<()> {
+    if entry.error.is_some() {
+        return route.abort(Network::ErrorReason::Failed);
+    }
+
+    let content = &entry.response.content;
+    let body = match (&content.text, content.encoding.as_deref()) {
+        (Some(text), Some("base64")) => base64::prelude::BASE64_STANDARD.decode(text)?,
+        (Some(text), _) => text.clone().into_bytes(),
+        (None, _) => vec![],
+    };
+    // the recorded body is already decoded, and may have a different length
+    let headers: Vec<(&str, &str)> = entry
+        .response
+        .headers
+        .iter()
+        .filter(|header| {
+            !header.name.eq_ignore_ascii_case("content-encoding")
+                && !header.name.eq_ignore_ascii_case("content-length")
+        })
+        .map(|header| (header.name.as_str(), header.value.as_str()))
+        .collect();
+    route.fulfill(entry.response.status, &headers, body)
+}
+
+/// A request seen by a `HarRecorder` which hasn't been turned into a `HarEntry` yet.
+#[derive(Clone)]
+struct PendingEntry {
+    request: Network::Request,
+    resource_type: Option<Network::ResourceType>,
+    wall_time: f64,
+    timestamp: f64,
+    response: Option<Network::Response>,
+    response_timestamp: Option<f64>,
+    redirect_url: Option<String>,
+    end_timestamp: Option<f64>,
+    data_length: u64,
+    encoded_data_length: u64,
+    transfer_size: Option<f64>,
+    error: Option<String>,
+    content: Option<GetResponseBodyReturnObject>,
+}
+
+/// Builds HAR entries from a tab's `Network` events.
+#[derive(Clone)]
+pub(crate) struct HarRecorder {
+    options: HarRecordingOptions,
+    entries: Vec<PendingEntry>,
+    /// Index in `entries` of each request which hasn't finished (or been redirected) yet
+    in_flight: HashMap<RequestId, usize>,
+}
+
+impl HarRecorder {
+    pub fn new(options: HarRecordingOptions) -> Self {
+        Self {
+            options,
+            entries: vec![],
+            in_flight: HashMap::new(),
+        }
+    }
+
+    /// Records `event`. `get_body` is called for finished requests when capturing content.
+    pub fn on_event<F>(&mut self, event: &Event, get_body: F)
+    where
+        F: FnOnce(RequestId) -> Result<GetResponseBodyReturnObject>,
+    {
+        match event {
+            Event::NetworkRequestWillBeSent(ev) => self.on_request(&ev.params),
+            Event::NetworkResponseReceived(ev) => {
+                if let Some(entry) = self.in_flight_entry(&ev.params.request_id) {
+                    entry.response = Some(ev.params.response.clone());
+                    entry.response_timestamp = Some(ev.params.timestamp);
+                }
+            }
+            Event::NetworkDataReceived(ev) => {
+                if let Some(entry) = self.in_flight_entry(&ev.params.request_id) {
+                    entry.data_length += u64::from(ev.params.data_length);
+                    entry.encoded_data_length += u64::from(ev.params.encoded_data_length);
+                }
+            }
+            Event::NetworkLoadingFinished(ev) => {
+                let capture_content = self.options.capture_content;
+                if let Some(index) = self.in_flight.remove(&ev.params.request_id) {
+                    let entry = &mut self.entries[index];
+                    entry.end_timestamp = Some(ev.params.timestamp);
+                    entry.transfer_size = Some(ev.params.encoded_data_length);
+                    if capture_content {
+                        entry.content = get_body(ev.params.request_id.clone()).ok();
+                    }
+                }
+            }
+            Event::NetworkLoadingFailed(ev) => {
+                if let Some(index) = self.in_flight.remove(&ev.params.request_id) {
+                    let entry = &mut self.entries[index];
+                    entry.end_timestamp = Some(ev.params.timestamp);
+                    entry.error = Some(ev.params.error_text.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_request(&mut self, params: &RequestWillBeSentEventParams) {
+        // a redirect reuses the request ID, and ends the previous request
+        if let Some(redirect_response) = &params.redirect_response {
+            if let Some(index) = self.in_flight.remove(&params.request_id) {
+                let entry = &mut self.entries[index];
+                entry.response = Some(redirect_response.clone());
+                entry.response_timestamp = Some(params.timestamp);
+                entry.end_timestamp = Some(params.timestamp);
+                entry.transfer_size = Some(redirect_response.encoded_data_length);
+                entry.redirect_url = Some(params.request.url.clone());
+            }
+        }
+
+        self.in_flight
+            .insert(params.request_id.clone(), self.entries.len());
+        self.entries.push(PendingEntry {
+            request: params.request.clone(),
+            resource_type: params.Type.clone(),
+            wall_time: params.wall_time,
+            timestamp: params.timestamp,
+            response: None,
+            response_timestamp: None,
+            redirect_url: None,
+            end_timestamp: None,
+            data_length: 0,
+            encoded_data_length: 0,
+            transfer_size: None,
+            error: None,
+            content: None,
+        });
+    }
+
+    fn in_flight_entry(&mut self, request_id: &RequestId) -> Option<&mut PendingEntry> {
+        let index = *self.in_flight.get(request_id)?;
+        Some(&mut self.entries[index])
+    }
+
+    /// Turns the requests which got a response or failed into a `Har`. Requests still waiting
+    /// for a response are left out.
+    pub fn finish(self) -> Har {
+        Har {
+            log: HarLog {
+                version: "1.2".to_string(),
+                creator: HarCreator {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries: self
+                    .entries
+                    .into_iter()
+                    .filter(|entry| entry.response.is_some() || entry.error.is_some())
+                    .map(PendingEntry::into_har_entry)
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl PendingEntry {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn into_har_entry(self) -> HarEntry {
+        let timings = self.timings();
+        let time = [
+            timings.blocked,
+            timings.dns,
+            timings.connect,
+            timings.send,
+            timings.wait,
+            timings.receive,
+        ]
+        .iter()
+        .filter(|duration| **duration > 0.0)
+        .sum();
+
+        let http_version = http_version(
+            self.response
+                .as_ref()
+                .and_then(|response| response.protocol.as_deref()),
+        );
+        // the headers which were actually sent, if Chrome knows them
+        let request_headers = header_list(
+            self.response
+                .as_ref()
+                .and_then(|response| response.request_headers.as_ref())
+                .unwrap_or(&self.request.headers),
+        );
+        let request = HarRequest {
+            cookies: request_headers
+                .iter()
+                .filter(|header| header.name.eq_ignore_ascii_case("cookie"))
+                .flat_map(|header| header.value.split(';'))
+                .filter_map(parse_cookie)
+                .collect(),
+            query_string: url::Url::parse(&self.request.url)
+                .map(|url| {
+                    url.query_pairs()
+                        .map(|(name, value)| HarQueryParam {
+                            name: name.to_string(),
+                            value: value.to_string(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            post_data: self.request.post_data.as_ref().map(|text| HarPostData {
+                mime_type: header_value(&request_headers, "content-type").unwrap_or_default(),
+                text: text.clone(),
+            }),
+            headers_size: -1,
+            body_size: self
+                .request
+                .post_data
+                .as_ref()
+                .map_or(0, |text| text.len() as i64),
+            method: self.request.method,
+            url: self.request.url,
+            http_version: http_version.clone(),
+            headers: request_headers,
+        };
+
+        let response = match self.response {
+            Some(response) => {
+                let headers = header_list(&response.headers);
+                let (text, encoding) = match self.content {
+                    Some(body) if body.base_64_encoded => (Some(body.body), Some("base64".into())),
+                    Some(body) => (Some(body.body), None),
+                    None => (None, None),
+                };
+                let transfer_size = self.transfer_size.map(|size| size as i64);
+                let body_size = self.encoded_data_length as i64;
+                HarResponse {
+                    status: response.status,
+                    status_text: response.status_text,
+                    http_version,
+                    cookies: headers
+                        .iter()
+                        .filter(|header| header.name.eq_ignore_ascii_case("set-cookie"))
+                        .filter_map(|header| parse_cookie(header.value.split(';').next()?))
+                        .collect(),
+                    content: HarContent {
+                        size: self.data_length as i64,
+                        mime_type: response.mime_type,
+                        text,
+                        encoding,
+                    },
+                    redirect_url: self.redirect_url.unwrap_or_default(),
+                    headers_size: transfer_size
+                        .filter(|size| *size >= body_size)
+                        .map_or(-1, |size| size - body_size),
+                    body_size,
+                    transfer_size,
+                    headers,
+                }
+            }
+            None => HarResponse {
+                status_text: self.error.clone().unwrap_or_default(),
+                headers_size: -1,
+                body_size: -1,
+                ..Default::default()
+            },
+        };
+
+        HarEntry {
+            started_date_time: format_wall_time(self.wall_time),
+            time,
+            request,
+            response,
+            cache: serde_json::Map::new(),
+            timings,
+            server_ip_address: None,
+            resource_type: self.resource_type.map(|resource_type| {
+                serde_json::to_value(resource_type)
+                    .ok()
+                    .and_then(|value| value.as_str().map(str::to_lowercase))
+                    .unwrap_or_default()
+            }),
+            error: self.error,
+        }
+    }
+
+    fn timings(&self) -> HarTimings {
+        let total = self
+            .end_timestamp
+            .map_or(0.0, |end| (end - self.timestamp) * 1000.0)
+            .max(0.0);
+        let response_timing = self
+            .response
+            .as_ref()
+            .and_then(|response| response.timing.as_ref());
+
+        let Some(timing) = response_timing else {
+            // e.g. cached responses, data URLs and failed requests
+            let wait = self.response_timestamp.map_or(total, |received| {
+                ((received - self.timestamp) * 1000.0).clamp(0.0, total)
+            });
+            return HarTimings {
+                wait,
+                receive: total - wait,
+                ..Default::default()
+            };
+        };
+
+        // `ResourceTiming` is in milliseconds since its `request_time`, with -1 for phases
+        // which didn't happen
+        let span = |start: f64, end: f64| {
+            if start >= 0.0 {
+                (end - start).max(0.0)
+            } else {
+                -1.0
+            }
+        };
+        let queued = ((timing.request_time - self.timestamp) * 1000.0).max(0.0);
+        let first_phase = [timing.dns_start, timing.connect_start, timing.send_start]
+            .into_iter()
+            .find(|start| *start >= 0.0)
+            .unwrap_or(0.0);
+        let blocked = queued + first_phase;
+        let dns = span(timing.dns_start, timing.dns_end);
+        let connect = span(timing.connect_start, timing.connect_end);
+        let send = span(timing.send_start, timing.send_end).max(0.0);
+        let wait = (timing.receive_headers_end - timing.send_end).max(0.0);
+        let receive = (total - (blocked + dns.max(0.0) + connect.max(0.0) + send + wait)).max(0.0);
+
+        HarTimings {
+            blocked,
+            dns,
+            connect,
+            send,
+            wait,
+            receive,
+            ssl: span(timing.ssl_start, timing.ssl_end),
+        }
+    }
+}
+
+/// Flattens CDP headers into HAR headers; Chrome joins repeated headers with newlines.
+fn header_list(headers: &Network::Headers) -> Vec<HarHeader> {
+    let Some(serde_json::Value::Object(map)) = &headers.0 else {
+        return vec![];
+    };
+    map.iter()
+        .flat_map(|(name, value)| {
+            let value = value
+                .as_str()
+                .map_or_else(|| value.to_string(), String::from);
+            value
+                .split('\n')
+                .map(|line| HarHeader {
+                    name: name.clone(),
+                    value: line.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn header_value(headers: &[HarHeader], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name))
+        .map(|header| header.value.clone())
+}
+
+fn parse_cookie(pair: &str) -> Option<HarCookie> {
+    let (name, value) = pair.trim().split_once('=')?;
+    Some(HarCookie {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn http_version(protocol: Option<&str>) -> String {
+    match protocol {
+        None => "HTTP/1.1".to_string(),
+        Some("h2") => "HTTP/2".to_string(),
+        Some("h3") => "HTTP/3".to_string(),
+        Some(protocol) => protocol.to_uppercase(),
+    }
+}
+
+/// Formats seconds since the Unix epoch as an ISO 8601 UTC date with milliseconds.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_wall_time(seconds: f64) -> String {
+    let millis = (seconds * 1000.0).round().max(0.0) as u64;
+    let (days, millis_of_day) = (millis / 86_400_000, millis % 86_400_000);
+
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        millis_of_day / 3_600_000,
+        millis_of_day / 60_000 % 60,
+        millis_of_day / 1000 % 60,
+        millis_of_day % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_wall_time;
+
+    #[test]
+    fn formats_wall_time() {
+        assert_eq!(format_wall_time(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_wall_time(951_782_400.5), "2000-02-29T00:00:00.500Z");
+        assert_eq!(
+            format_wall_time(1_714_564_800.123),
+            "2024-05-01T12:00:00.123Z"
+        );
+    }
+}
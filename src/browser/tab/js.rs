@@ -0,0 +1,276 @@
+use std::fmt::Debug;
+
+use anyhow::Result;
+
+use thiserror::Error;
+
+use log::debug;
+
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::Value as Json;
+
+use super::Tab;
+use super::element::Element;
+use crate::protocol::cdp::Runtime;
+
+/// The key under which `Element`s and `JsHandle`s serialize their remote object ID, so that
+/// `call_function` can pass them by reference.
+const OBJECT_ID_KEY: &str = "$headlessChromeObjectId";
+
+/// An exception thrown by JavaScript run through `evaluate_typed`, `call_function` and
+/// friends, built from the protocol's `exceptionDetails`.
+#[derive(Debug, Clone, Error)]
+#[error("JavaScript exception: {message}")]
+pub struct JsException {
+    /// E.g. `TypeError: x is undefined`, or the thrown value if it wasn't an `Error`
+    pub message: String,
+    /// The stack as formatted by V8, including the message
+    pub stack: Option<String>,
+    pub stack_trace: Option<Runtime::StackTrace>,
+    pub url: Option<String>,
+    /// Zero-based
+    pub line_number: u32,
+    /// Zero-based
+    pub column_number: u32,
+}
+
+impl From<Runtime::ExceptionDetails> for JsException {
+    fn from(details: Runtime::ExceptionDetails) -> Self {
+        let description = details
+            .exception
+            .as_ref()
+            .and_then(|exception| exception.description.clone());
+        let thrown_value = details
+            .exception
+            .and_then(|exception| exception.value)
+            .map(|value| match value {
+                Json::String(string) => string,
+                other => other.to_string(),
+            });
+
+        let message = description
+            .as_deref()
+            .and_then(|description| description.lines().next())
+            .map(ToString::to_string)
+            .or(thrown_value)
+            .unwrap_or(details.text);
+
+        Self {
+            message,
+            stack: description.filter(|description| description.contains('\n')),
+            stack_trace: details.stack_trace,
+            url: details.url,
+            line_number: details.line_number,
+            column_number: details.column_number,
+        }
+    }
+}
+
+/// A reference to a JavaScript value living in the page, as returned by
+/// `Tab::evaluate_handle`.
+///
+/// Handles can be passed to `call_function` like any other argument, and the value is only
+/// copied out of the page when asked for with `json_value`. The page's object is released
+/// when the handle is dropped.
+pub struct JsHandle<'a> {
+    tab: &'a Tab,
+    pub remote_object: Runtime::RemoteObject,
+}
+
+impl Debug for JsHandle<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "JsHandle {}",
+            self.remote_object.description.as_deref().unwrap_or("")
+        )
+    }
+}
+
+impl<'a> JsHandle<'a> {
+    pub(crate) fn new(tab: &'a Tab, remote_object: Runtime::RemoteObject) -> Self {
+        Self { tab, remote_object }
+    }
+
+    /// Copies the value out of the page and deserializes it.
+    pub fn json_value<T: DeserializeOwned>(&self) -> Result<T> {
+        match &self.remote_object.object_id {
+            Some(object_id) => call_function_on(
+                self.tab,
+                object_id.clone(),
+                "function() { return this; }",
+                (),
+            ),
+            None => Ok(serde_json::from_value(
+                self.remote_object.value.clone().unwrap_or_default(),
+            )?),
+        }
+    }
+
+    /// Calls `function_declaration` with `this` bound to this handle's value; see
+    /// `Tab::call_function`.
+    pub fn call_function<A: CallArgs, R: DeserializeOwned>(
+        &self,
+        function_declaration: &str,
+        args: A,
+    ) -> Result<R> {
+        let object_id = self.remote_object.object_id.clone().ok_or_else(|| {
+            anyhow::anyhow!("can't call a function on a primitive value; use json_value instead")
+        })?;
+        call_function_on(self.tab, object_id, function_declaration, args)
+    }
+
+    /// The element this handle refers to, or `None` if it isn't a DOM node.
+    pub fn as_element(&self) -> Result<Option<Element<'a>>> {
+        match &self.remote_object.object_id {
+            Some(object_id)
+                if self.remote_object.subtype == Some(Runtime::RemoteObjectSubtype::Node) =>
+            {
+                let node_id = self
+                    .tab
+                    .call_method(crate::protocol::cdp::DOM::RequestNode {
+                        object_id: object_id.clone(),
+                    })?
+                    .node_id;
+                Ok(Some(Element::new(self.tab, node_id)?))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Serialize for JsHandle<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.remote_object.object_id {
+            Some(object_id) => serialize_object_id(object_id, serializer),
+            None => self.remote_object.value.serialize(serializer),
+        }
+    }
+}
+
+impl Drop for JsHandle<'_> {
+    fn drop(&mut self) {
+        if let Some(object_id) = self.remote_object.object_id.take() {
+            if let Err(error) = self.tab.call_method(Runtime::ReleaseObject { object_id }) {
+                debug!("Failed to release JS handle's remote object: {error}");
+            }
+        }
+    }
+}
+
+pub(crate) fn serialize_object_id<S: Serializer>(
+    object_id: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry(OBJECT_ID_KEY, object_id)?;
+    map.end()
+}
+
+/// Positional arguments for `call_function`: a tuple with one element per argument, e.g.
+/// `()`, `(value,)` or `(a, b)`, whose elements are `Serialize`.
+///
+/// Each element is exactly one argument, so `(vec![1, 2],)` passes a single array and
+/// `(None::<u32>,)` passes `null`. `Element`s and `JsHandle`s are passed by reference, but only
+/// as elements of the tuple themselves; nested in another value they make the call fail.
+pub trait CallArgs {
+    fn into_call_arguments(self) -> Result<Vec<Runtime::CallArgument>>;
+}
+
+macro_rules! impl_call_args {
+    ($($arg:ident),*) => {
+        impl<$($arg: Serialize),*> CallArgs for ($($arg,)*) {
+            #[allow(non_snake_case)]
+            fn into_call_arguments(self) -> Result<Vec<Runtime::CallArgument>> {
+                let ($($arg,)*) = self;
+                Ok(vec![$(to_call_argument(serde_json::to_value($arg)?)?),*])
+            }
+        }
+    };
+}
+
+impl_call_args!();
+impl_call_args!(A);
+impl_call_args!(A, B);
+impl_call_args!(A, B, C);
+impl_call_args!(A, B, C, D);
+impl_call_args!(A, B, C, D, E);
+impl_call_args!(A, B, C, D, E, F);
+impl_call_args!(A, B, C, D, E, F, G);
+impl_call_args!(A, B, C, D, E, F, G, H);
+
+/// Passes `value` by reference if it's a serialized `Element` or `JsHandle`, and by value
+/// otherwise.
+fn to_call_argument(value: Json) -> Result<Runtime::CallArgument> {
+    if let Some(object_id) = as_object_id(&value) {
+        return Ok(Runtime::CallArgument {
+            value: None,
+            unserializable_value: None,
+            object_id: Some(object_id.to_string()),
+        });
+    }
+    if contains_object_id(&value) {
+        return Err(anyhow::anyhow!(
+            "Elements and JsHandles can only be passed as arguments of their own, not inside \
+             other values"
+        ));
+    }
+    Ok(Runtime::CallArgument {
+        value: Some(value),
+        unserializable_value: None,
+        object_id: None,
+    })
+}
+
+fn as_object_id(value: &Json) -> Option<&str> {
+    value
+        .as_object()
+        .filter(|map| map.len() == 1)
+        .and_then(|map| map.get(OBJECT_ID_KEY))
+        .and_then(Json::as_str)
+}
+
+fn contains_object_id(value: &Json) -> bool {
+    match value {
+        Json::Array(values) => values
+            .iter()
+            .any(|value| as_object_id(value).is_some() || contains_object_id(value)),
+        Json::Object(map) => map
+            .values()
+            .any(|value| as_object_id(value).is_some() || contains_object_id(value)),
+        _ => false,
+    }
+}
+
+/// Calls `function_declaration` with `this` bound to the object `object_id`, awaiting any
+/// promise it returns, and deserializes the result.
+pub(crate) fn call_function_on<A: CallArgs, R: DeserializeOwned>(
+    tab: &Tab,
+    object_id: String,
+    function_declaration: &str,
+    args: A,
+) -> Result<R> {
+    let result = tab.call_method(Runtime::CallFunctionOn {
+        object_id: Some(object_id),
+        function_declaration: function_declaration.to_string(),
+        arguments: Some(args.into_call_arguments()?),
+        return_by_value: Some(true),
+        generate_preview: None,
+        silent: Some(true),
+        await_promise: Some(true),
+        user_gesture: None,
+        execution_context_id: None,
+        object_group: None,
+        throw_on_side_effect: None,
+        serialization_options: None,
+        unique_context_id: None,
+    })?;
+
+    if let Some(details) = result.exception_details {
+        return Err(JsException::from(details).into());
+    }
+    Ok(serde_json::from_value(
+        result.result.value.unwrap_or_default(),
+    )?)
+}
@@ -1547,3 +1547,389 @@ pub fn get_key_definition(key: &str) -> Result<&KeyDefinition> {
         .into())
     }
 }
+
+/// Looks a key up by its `code` (e.g. `ShiftRight`, `Numpad1`) in the US table.
+pub fn get_key_definition_by_code(code: &str) -> Option<&'static KeyDefinition> {
+    USKEYBOARD_LAYOUT
+        .iter()
+        .find(|key_definition| key_definition.code == code)
+}
+
+/// A printable key on a keyboard layout: its physical position (`code`) and the characters it
+/// types on its own, with Shift, and with AltGr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutKey {
+    pub code: &'static str,
+    /// The Windows virtual key code, which is what `KeyboardEvent.keyCode` reports
+    pub key_code: JsUInt,
+    pub normal: &'static str,
+    pub shifted: &'static str,
+    pub alt_gr: Option<&'static str>,
+}
+
+impl LayoutKey {
+    pub const fn new(
+        code: &'static str,
+        key_code: JsUInt,
+        normal: &'static str,
+        shifted: &'static str,
+    ) -> Self {
+        Self {
+            code,
+            key_code,
+            normal,
+            shifted,
+            alt_gr: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_alt_gr(mut self, alt_gr: &'static str) -> Self {
+        self.alt_gr = Some(alt_gr);
+        self
+    }
+}
+
+/// Which modifiers a character needs on a given layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLevel {
+    Normal,
+    Shifted,
+    AltGr,
+}
+
+/// Maps the characters typed by `Tab::type_str` (and the code names understood by
+/// `Tab::key_down`, like `KeyZ`) to physical keys.
+///
+/// Keys which don't type anything (`Enter`, `ArrowLeft`, `Control`, ...) are the same on every
+/// layout. Besides the built-in layouts, custom ones can be assembled with
+/// `KeyboardLayout::with_keys`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    keys: Vec<LayoutKey>,
+}
+
+// `USKEYBOARD_LAYOUT` only has `*` and `+` on the numpad.
+const US_SHIFTED_SYMBOLS: [LayoutKey; 2] = [
+    LayoutKey::new("Digit8", 56, "8", "*"),
+    LayoutKey::new("Equal", 187, "=", "+"),
+];
+
+const UK_KEYS: [LayoutKey; 7] = [
+    LayoutKey::new("Backquote", 223, "`", "¬").with_alt_gr("¦"),
+    LayoutKey::new("Digit2", 50, "2", "\""),
+    LayoutKey::new("Digit3", 51, "3", "£"),
+    LayoutKey::new("Digit4", 52, "4", "$").with_alt_gr("€"),
+    LayoutKey::new("Quote", 192, "'", "@"),
+    LayoutKey::new("Backslash", 222, "#", "~"),
+    LayoutKey::new("IntlBackslash", 220, "\\", "|"),
+];
+
+// Dead keys (like `^` and `´`) are typed as if they were regular keys.
+const GERMAN_KEYS: [LayoutKey; 24] = [
+    LayoutKey::new("Digit2", 50, "2", "\"").with_alt_gr("²"),
+    LayoutKey::new("Digit3", 51, "3", "§").with_alt_gr("³"),
+    LayoutKey::new("Digit6", 54, "6", "&"),
+    LayoutKey::new("Digit7", 55, "7", "/").with_alt_gr("{"),
+    LayoutKey::new("Digit8", 56, "8", "(").with_alt_gr("["),
+    LayoutKey::new("Digit9", 57, "9", ")").with_alt_gr("]"),
+    LayoutKey::new("Digit0", 48, "0", "=").with_alt_gr("}"),
+    LayoutKey::new("Minus", 219, "ß", "?").with_alt_gr("\\"),
+    LayoutKey::new("KeyQ", 81, "q", "Q").with_alt_gr("@"),
+    LayoutKey::new("KeyE", 69, "e", "E").with_alt_gr("€"),
+    LayoutKey::new("KeyY", 90, "z", "Z"),
+    LayoutKey::new("KeyZ", 89, "y", "Y"),
+    LayoutKey::new("KeyM", 77, "m", "M").with_alt_gr("µ"),
+    LayoutKey::new("BracketLeft", 186, "ü", "Ü"),
+    LayoutKey::new("BracketRight", 187, "+", "*").with_alt_gr("~"),
+    LayoutKey::new("Semicolon", 192, "ö", "Ö"),
+    LayoutKey::new("Quote", 222, "ä", "Ä"),
+    LayoutKey::new("Backslash", 191, "#", "'"),
+    LayoutKey::new("IntlBackslash", 226, "<", ">").with_alt_gr("|"),
+    LayoutKey::new("Comma", 188, ",", ";"),
+    LayoutKey::new("Period", 190, ".", ":"),
+    LayoutKey::new("Slash", 189, "-", "_"),
+    LayoutKey::new("Backquote", 220, "^", "°"),
+    LayoutKey::new("Equal", 221, "´", "`"),
+];
+
+const FRENCH_KEYS: [LayoutKey; 28] = [
+    LayoutKey::new("Backquote", 222, "²", "²"),
+    LayoutKey::new("Digit1", 49, "&", "1"),
+    LayoutKey::new("Digit2", 50, "é", "2"),
+    LayoutKey::new("Digit3", 51, "\"", "3").with_alt_gr("#"),
+    LayoutKey::new("Digit4", 52, "'", "4").with_alt_gr("{"),
+    LayoutKey::new("Digit5", 53, "(", "5").with_alt_gr("["),
+    LayoutKey::new("Digit6", 54, "-", "6").with_alt_gr("|"),
+    LayoutKey::new("Digit7", 55, "è", "7"),
+    LayoutKey::new("Digit8", 56, "_", "8").with_alt_gr("\\"),
+    LayoutKey::new("Digit9", 57, "ç", "9").with_alt_gr("^"),
+    LayoutKey::new("Digit0", 48, "à", "0").with_alt_gr("@"),
+    LayoutKey::new("Minus", 219, ")", "°").with_alt_gr("]"),
+    LayoutKey::new("Equal", 187, "=", "+").with_alt_gr("}"),
+    LayoutKey::new("KeyQ", 65, "a", "A"),
+    LayoutKey::new("KeyW", 90, "z", "Z"),
+    LayoutKey::new("KeyE", 69, "e", "E").with_alt_gr("€"),
+    LayoutKey::new("BracketLeft", 221, "^", "¨"),
+    LayoutKey::new("KeyA", 81, "q", "Q"),
+    LayoutKey::new("Semicolon", 77, "m", "M"),
+    LayoutKey::new("Quote", 192, "ù", "%"),
+    LayoutKey::new("Backslash", 220, "*", "µ"),
+    LayoutKey::new("KeyZ", 87, "w", "W"),
+    LayoutKey::new("KeyM", 188, ",", "?"),
+    LayoutKey::new("Comma", 190, ";", "."),
+    LayoutKey::new("Period", 191, ":", "/"),
+    LayoutKey::new("Slash", 223, "!", "§"),
+    LayoutKey::new("BracketRight", 186, "$", "£").with_alt_gr("¤"),
+    LayoutKey::new("IntlBackslash", 226, "<", ">"),
+];
+
+impl Default for KeyboardLayout {
+    fn default() -> Self {
+        Self::us()
+    }
+}
+
+impl KeyboardLayout {
+    /// A layout with just the given keys.
+    pub fn new(keys: Vec<LayoutKey>) -> Self {
+        Self { keys }
+    }
+
+    /// The printable keys of `USKEYBOARD_LAYOUT`, outside the numpad.
+    pub fn us() -> Self {
+        let mut keys: Vec<LayoutKey> = vec![];
+        for definition in USKEYBOARD_LAYOUT.iter().filter(|definition| {
+            definition.key.chars().count() == 1 && !definition.code.starts_with("Numpad")
+        }) {
+            match keys.iter_mut().find(|key| key.code == definition.code) {
+                // the unshifted character comes first, then the shifted one (if any)
+                Some(key) if key.shifted == key.normal => key.shifted = definition.key,
+                Some(_) => {}
+                None => keys.push(LayoutKey::new(
+                    definition.code,
+                    definition.key_code,
+                    definition.key,
+                    definition.key,
+                )),
+            }
+        }
+        Self::new(keys).with_keys(&US_SHIFTED_SYMBOLS)
+    }
+
+    pub fn uk() -> Self {
+        Self::us().with_keys(&UK_KEYS)
+    }
+
+    pub fn german() -> Self {
+        Self::us().with_keys(&GERMAN_KEYS)
+    }
+
+    pub fn french() -> Self {
+        Self::us().with_keys(&FRENCH_KEYS)
+    }
+
+    /// Replaces the keys with the same `code`s as `keys`, and adds the others.
+    #[must_use]
+    pub fn with_keys(mut self, keys: &[LayoutKey]) -> Self {
+        for key in keys {
+            match self.keys.iter_mut().find(|k| k.code == key.code) {
+                Some(existing) => *existing = *key,
+                None => self.keys.push(*key),
+            }
+        }
+        self
+    }
+
+    /// Finds the key which types `character`, preferring the one needing the fewest modifiers.
+    pub fn find_character(&self, character: &str) -> Option<(&LayoutKey, KeyLevel)> {
+        let find = |level: KeyLevel| {
+            self.keys.iter().find_map(|key| {
+                let typed = match level {
+                    KeyLevel::Normal => Some(key.normal),
+                    KeyLevel::Shifted => Some(key.shifted),
+                    KeyLevel::AltGr => key.alt_gr,
+                };
+                (typed == Some(character)).then_some((key, level))
+            })
+        };
+        find(KeyLevel::Normal)
+            .or_else(|| find(KeyLevel::Shifted))
+            .or_else(|| find(KeyLevel::AltGr))
+    }
+
+    pub fn find_code(&self, code: &str) -> Option<&LayoutKey> {
+        self.keys.iter().find(|key| key.code == code)
+    }
+}
+
+/// Everything `Input.dispatchKeyEvent` needs to know about a key.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyDescription {
+    pub key: String,
+    pub code: String,
+    pub key_code: JsUInt,
+    pub text: Option<String>,
+    pub location: Option<JsUInt>,
+}
+
+/// Resolves `name` to a key, trying (in this order) a code on `layout` (e.g. `KeyZ`, typing
+/// whatever the held modifiers make it type), a character on `layout` (e.g. `z`), and finally
+/// the name or code of a non-printable key (e.g. `Enter`, `ShiftRight`).
+pub(crate) fn describe_key(
+    layout: &KeyboardLayout,
+    name: &str,
+    shifted: bool,
+    alt_gr: bool,
+) -> Result<KeyDescription> {
+    if let Some(key) = layout.find_code(name) {
+        let typed = match (alt_gr, shifted) {
+            (true, _) => key.alt_gr.unwrap_or(key.normal),
+            (false, true) => key.shifted,
+            (false, false) => key.normal,
+        };
+        return Ok(KeyDescription {
+            key: typed.to_string(),
+            code: key.code.to_string(),
+            key_code: key.key_code,
+            text: Some(typed.to_string()),
+            location: None,
+        });
+    }
+
+    if let Some((key, _)) = layout.find_character(name) {
+        return Ok(KeyDescription {
+            key: name.to_string(),
+            code: key.code.to_string(),
+            key_code: key.key_code,
+            text: Some(name.to_string()),
+            location: None,
+        });
+    }
+
+    let definition = match get_key_definition_by_code(name) {
+        Some(definition) => definition,
+        None => get_key_definition(name)?,
+    };
+    let location = if definition.code.starts_with("Numpad") {
+        Some(3)
+    } else if MODIFIER_KEYS.contains(&definition.key) {
+        if definition.code.ends_with("Left") {
+            Some(1)
+        } else if definition.code.ends_with("Right") {
+            Some(2)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    Ok(KeyDescription {
+        key: definition.key.to_string(),
+        code: definition.code.to_string(),
+        key_code: definition.key_code,
+        text: definition
+            .text
+            .or((definition.key.len() == 1).then_some(definition.key))
+            .map(ToString::to_string),
+        location,
+    })
+}
+
+const MODIFIER_KEYS: [&str; 4] = ["Alt", "Control", "Meta", "Shift"];
+
+/// The `Input.dispatchKeyEvent` modifier bit set while `key` is held, or 0 if it isn't a
+/// modifier.
+pub(crate) fn modifier_bit(key: &str) -> JsUInt {
+    match key {
+        "Alt" => 1,
+        "Control" => 2,
+        "Meta" => 4,
+        "Shift" => 8,
+        _ => 0,
+    }
+}
+
+/// Splits a chord like `Control+Shift+KeyK` into its keys. A `+` which can't be a separator
+/// is the key itself, so `Control++` is Control and `+`.
+pub(crate) fn split_chord(chord: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut building = String::new();
+    for c in chord.chars() {
+        if c == '+' && !building.is_empty() {
+            keys.push(std::mem::take(&mut building));
+        } else {
+            building.push(c);
+        }
+    }
+    keys.push(building);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyLevel, KeyboardLayout, describe_key, split_chord};
+
+    #[test]
+    fn chords_are_split_on_plus() {
+        assert_eq!(
+            split_chord("Control+Shift+KeyK"),
+            ["Control", "Shift", "KeyK"]
+        );
+        assert_eq!(split_chord("Control++"), ["Control", "+"]);
+        assert_eq!(split_chord("+"), ["+"]);
+        assert_eq!(split_chord("Enter"), ["Enter"]);
+    }
+
+    #[test]
+    fn layouts_place_characters_on_their_own_keys() {
+        let us = KeyboardLayout::us();
+        let (key, level) = us.find_character("@").unwrap();
+        assert_eq!((key.code, level), ("Digit2", KeyLevel::Shifted));
+        let (key, level) = us.find_character("a").unwrap();
+        assert_eq!(
+            (key.code, key.key_code, level),
+            ("KeyA", 65, KeyLevel::Normal)
+        );
+        assert_eq!(us.find_character("*").unwrap().0.code, "Digit8");
+        assert_eq!(us.find_code("Space").unwrap().shifted, " ");
+        assert!(us.find_code("Numpad1").is_none());
+
+        let uk = KeyboardLayout::uk();
+        let (key, level) = uk.find_character("@").unwrap();
+        assert_eq!((key.code, level), ("Quote", KeyLevel::Shifted));
+
+        let german = KeyboardLayout::german();
+        let (key, level) = german.find_character("@").unwrap();
+        assert_eq!((key.code, level), ("KeyQ", KeyLevel::AltGr));
+        assert_eq!(german.find_character("z").unwrap().0.code, "KeyY");
+        assert!(german.find_character("[").is_some());
+
+        let french = KeyboardLayout::french();
+        let (key, level) = french.find_character("1").unwrap();
+        assert_eq!((key.code, level), ("Digit1", KeyLevel::Shifted));
+        assert_eq!(french.find_character("a").unwrap().0.code, "KeyQ");
+    }
+
+    #[test]
+    fn keys_are_described_by_code_character_or_name() {
+        let german = KeyboardLayout::german();
+
+        let key = describe_key(&german, "KeyY", false, false).unwrap();
+        assert_eq!((key.key.as_str(), key.key_code), ("z", 90));
+        let key = describe_key(&german, "KeyY", true, false).unwrap();
+        assert_eq!(key.key, "Z");
+        let key = describe_key(&german, "KeyQ", false, true).unwrap();
+        assert_eq!(key.text.as_deref(), Some("@"));
+
+        let key = describe_key(&german, "ShiftRight", false, false).unwrap();
+        assert_eq!((key.key.as_str(), key.location), ("Shift", Some(2)));
+        let key = describe_key(&german, "Enter", false, false).unwrap();
+        assert_eq!(
+            (key.code.as_str(), key.text.as_deref()),
+            ("Enter", Some("\r"))
+        );
+        assert!(describe_key(&german, "NoSuchKey", false, false).is_err());
+    }
+}
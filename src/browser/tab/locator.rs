@@ -0,0 +1,303 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+
+use thiserror::Error;
+
+use log::debug;
+
+use serde_json::json;
+
+use super::element::{Checks, Element};
+use super::{NoElementFound, Tab};
+use crate::types::RemoteError;
+use crate::util;
+
+#[derive(Debug, Error)]
+#[error("Element is not actionable: {reason}")]
+pub struct ElementNotActionable {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Css(String),
+    Nth(usize),
+    Last,
+    HasText(String),
+}
+
+/// A lazy way of finding element(s) on a page.
+///
+/// Unlike an `Element`, which points at one specific DOM node and goes stale once the page
+/// re-renders it, a `Locator` only stores how to find its element. The selector chain is
+/// resolved again for every action, and each action waits (up to the tab's default timeout, see
+/// `Tab::set_default_timeout`) until the element is attached and, where it matters, visible,
+/// stable and enabled.
+///
+/// If a locator matches several elements, actions apply to the first one; use `nth`, `last` or
+/// `filter` to narrow it down.
+///
+/// ```rust,no_run
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// #
+/// use headless_chrome::Browser;
+/// let browser = Browser::default()?;
+/// let tab = browser.new_tab()?;
+/// tab.navigate_to("https://www.wikipedia.org")?;
+///
+/// let search = tab.locator("form#search-form").locator("input");
+/// search.nth(0).fill("WebKit")?;
+/// tab.locator("button").filter("Search").click()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Locator<'a> {
+    tab: &'a Tab,
+    steps: Vec<Step>,
+    timeout: Option<Duration>,
+}
+
+impl Debug for Locator<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "Locator {:?}", self.steps)
+    }
+}
+
+impl<'a> Locator<'a> {
+    pub(crate) fn new(tab: &'a Tab, selector: &str) -> Self {
+        Self {
+            tab,
+            steps: vec![Step::Css(selector.to_string())],
+            timeout: None,
+        }
+    }
+
+    fn with_step(&self, step: Step) -> Self {
+        let mut locator = self.clone();
+        locator.steps.push(step);
+        locator
+    }
+
+    /// Finds elements matching `selector` inside the elements matched by this locator.
+    #[must_use]
+    pub fn locator(&self, selector: &str) -> Self {
+        self.with_step(Step::Css(selector.to_string()))
+    }
+
+    /// Narrows this locator down to its `index`th match (zero-based).
+    #[must_use]
+    pub fn nth(&self, index: usize) -> Self {
+        self.with_step(Step::Nth(index))
+    }
+
+    #[must_use]
+    pub fn first(&self) -> Self {
+        self.nth(0)
+    }
+
+    #[must_use]
+    pub fn last(&self) -> Self {
+        self.with_step(Step::Last)
+    }
+
+    /// Narrows this locator down to matches whose `innerText` contains `has_text`.
+    #[must_use]
+    pub fn filter(&self, has_text: &str) -> Self {
+        self.with_step(Step::HasText(has_text.to_string()))
+    }
+
+    /// Overrides the tab's default timeout for actions on this locator.
+    #[must_use]
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut locator = self.clone();
+        locator.timeout = Some(timeout);
+        locator
+    }
+
+    /// Resolves the selector chain against the current DOM.
+    ///
+    /// This doesn't wait: if nothing matches right now, the result is empty.
+    pub fn all(&self) -> Result<Vec<Element<'a>>> {
+        let mut matches: Option<Vec<Element<'a>>> = None;
+
+        for step in &self.steps {
+            matches = Some(match step {
+                Step::Css(selector) => match matches {
+                    None => {
+                        let root_node_id = self.tab.get_document()?.node_id;
+                        self.tab
+                            .run_query_selector_all_on_node(root_node_id, selector)?
+                    }
+                    Some(parents) => {
+                        let mut seen = HashSet::new();
+                        let mut children = vec![];
+                        for parent in parents {
+                            for child in parent.find_elements(selector)? {
+                                if seen.insert(child.backend_node_id) {
+                                    children.push(child);
+                                }
+                            }
+                        }
+                        children
+                    }
+                },
+                Step::Nth(index) => matches
+                    .unwrap_or_default()
+                    .into_iter()
+                    .nth(*index)
+                    .into_iter()
+                    .collect(),
+                Step::Last => matches
+                    .unwrap_or_default()
+                    .into_iter()
+                    .last()
+                    .into_iter()
+                    .collect(),
+                Step::HasText(text) => {
+                    let mut filtered = vec![];
+                    for element in matches.unwrap_or_default() {
+                        let has_text = element
+                            .call_js_fn(
+                                "function(text) { return (this.innerText || this.textContent || '').includes(text); }",
+                                vec![json!(text)],
+                                false,
+                            )?
+                            .value
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false);
+                        if has_text {
+                            filtered.push(element);
+                        }
+                    }
+                    filtered
+                }
+            });
+        }
+
+        Ok(matches.unwrap_or_default())
+    }
+
+    /// Waits until this locator matches at least one element, like the other actions, and
+    /// returns the number of matches. Fails with a timeout if nothing matches in time; use
+    /// `count_now` to check for the absence of elements.
+    pub fn count(&self) -> Result<usize> {
+        util::Wait::with_timeout(self.timeout()).strict_until(
+            || {
+                let count = self.all().map_err(Self::map_stale)?.len();
+                if count == 0 {
+                    return Err(NoElementFound {}.into());
+                }
+                Ok(count)
+            },
+            Self::retryable,
+        )
+    }
+
+    /// The number of elements matching this locator right now, which may be 0. Doesn't wait.
+    pub fn count_now(&self) -> Result<usize> {
+        Ok(self.all()?.len())
+    }
+
+    /// Waits until this locator matches an element which is attached to the DOM, and returns it.
+    ///
+    /// The returned `Element` is a snapshot: it won't follow the DOM if the page re-renders.
+    pub fn element(&self) -> Result<Element<'a>> {
+        self.wait_for(Checks::ATTACHED)
+    }
+
+    /// Waits until the element is visible, stable and enabled, then clicks it.
+    pub fn click(&self) -> Result<&Self> {
+        self.act(Checks::ALL, |element| {
+            element.click()?;
+            Ok(())
+        })?;
+        Ok(self)
+    }
+
+    /// Waits until the element is visible, stable and enabled, then replaces its value with
+    /// `text`; see `Element::fill`.
+    pub fn fill(&self, text: &str) -> Result<&Self> {
+        self.act(Checks::ALL, |element| {
+            element.fill(text)?;
+            Ok(())
+        })?;
+        Ok(self)
+    }
+
+    /// Waits until the element is attached, then returns its `innerText`.
+    pub fn text(&self) -> Result<String> {
+        self.act(Checks::ATTACHED, Element::get_inner_text)
+    }
+
+    /// Resolves the locator and runs `action` on the first match, retrying (until the timeout)
+    /// while the element is missing, not actionable yet, or goes stale in the meantime.
+    fn act<T, F>(&self, checks: Checks, action: F) -> Result<T>
+    where
+        F: Fn(&Element<'a>) -> Result<T>,
+    {
+        debug!("Acting on locator {:?}", self.steps);
+        util::Wait::with_timeout(self.timeout()).strict_until(
+            || {
+                let element = self.resolve_actionable(checks)?;
+                action(&element).map_err(Self::map_stale)
+            },
+            Self::retryable,
+        )
+    }
+
+    fn wait_for(&self, checks: Checks) -> Result<Element<'a>> {
+        util::Wait::with_timeout(self.timeout())
+            .strict_until(|| self.resolve_actionable(checks), Self::retryable)
+    }
+
+    fn resolve_actionable(&self, checks: Checks) -> Result<Element<'a>> {
+        let element = self
+            .all()
+            .map_err(Self::map_stale)?
+            .into_iter()
+            .next()
+            .ok_or(NoElementFound {})?;
+
+        element.check_actionable(checks).map_err(Self::map_stale)?;
+        Ok(element)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+            .unwrap_or_else(|| *self.tab.default_timeout.read().unwrap())
+    }
+
+    /// The DOM changing underneath us shows up as a variety of protocol errors; they all mean
+    /// that the element we resolved has gone away and we should look it up again.
+    fn map_stale(error: Error) -> Error {
+        match error.downcast::<RemoteError>() {
+            Ok(remote_error) => match remote_error.message.as_ref() {
+                "Could not find node with given id"
+                | "No node with given id found"
+                | "Node with given id does not belong to the document"
+                | "Could not find object with given id"
+                | "Cannot find context with specified id" => ElementNotActionable {
+                    reason: "element went stale".to_string(),
+                }
+                .into(),
+                _ => remote_error.into(),
+            },
+            Err(original_error) => NoElementFound::map(original_error),
+        }
+    }
+
+    fn retryable(error: Error) -> Result<()> {
+        if error.is::<NoElementFound>() || error.is::<ElementNotActionable>() {
+            debug!("Retrying locator action: {error}");
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
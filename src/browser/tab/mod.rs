@@ -1,9 +1,10 @@
-use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak, mpsc};
 use std::thread;
 use std::time::Duration;
 use std::{
     collections::HashMap,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
 use anyhow::{Error, Result};
@@ -17,11 +18,20 @@ use serde::de::DeserializeOwned;
 use serde_json::{Value as Json, json};
 
 use dialog::Dialog;
-use element::Element;
+use element::{Element, ElementHandle, FillFormError, FormValue};
+use har::{Har, HarNotFound, HarRecorder, HarRecordingOptions, HarReplayOptions};
+use js::{CallArgs, JsException, JsHandle};
+use keys::KeyboardLayout;
+use locator::Locator;
+use network::{BlockedRequestCounts, NetworkConditions, NetworkResponse, UrlMatcher};
+use network_log::{NetworkLog, NetworkLogOptions};
 use point::Point;
+use route::{Handler, RegisteredRoute, ResponseRoute, Route, RouteId};
+use websocket::{WebSocket, WebSocketMocks, WebSocketRoute, WebSocketTracker};
 
 use crate::protocol::cdp::{
-    Browser, DOM, Debugger, Emulation, Fetch, Input, Log, Network, Page, Profiler, Runtime, Target,
+    Accessibility, Browser, DOM, Debugger, Emulation, Fetch, Input, Log, Network, Page, Profiler,
+    Runtime, Storage, Target,
     types::{Event, Method},
 };
 
@@ -29,8 +39,6 @@ use Runtime::AddBinding;
 
 use base64::Engine;
 
-use Input::DispatchKeyEvent;
-
 use Page::{AddScriptToEvaluateOnNewDocument, Navigate, SetInterceptFileChooserDialog};
 
 use Target::AttachToTarget;
@@ -47,8 +55,9 @@ use Fetch::{
 };
 
 use Network::{
-    Cookie, GetResponseBody, GetResponseBodyReturnObject, SetExtraHTTPHeaders,
-    SetUserAgentOverride, events::LoadingFailedEventParams, events::ResponseReceivedEventParams,
+    Cookie, GetResponseBodyReturnObject, SetExtraHTTPHeaders, SetUserAgentOverride,
+    events::LoadingFailedEventParams, events::RequestWillBeSentEventParams,
+    events::ResponseReceivedEventParams,
 };
 
 use crate::util;
@@ -61,8 +70,15 @@ use std::thread::sleep;
 
 pub mod dialog;
 pub mod element;
-mod keys;
+pub mod har;
+pub mod js;
+pub mod keys;
+pub mod locator;
+pub mod network;
+pub mod network_log;
 pub mod point;
+pub mod route;
+pub mod websocket;
 
 #[derive(Debug, Copy, Clone)]
 pub enum ModifierKey {
@@ -72,6 +88,95 @@ pub enum ModifierKey {
     Shift = 8,
 }
 
+impl ModifierKey {
+    fn key_name(self) -> &'static str {
+        match self {
+            Self::Alt => "Alt",
+            Self::Ctrl => "Control",
+            Self::Meta => "Meta",
+            Self::Shift => "Shift",
+        }
+    }
+}
+
+/// Which way the finger moves in `Tab::swipe_from_point` and `Element::swipe`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// How to click something, see `Tab::click_point_with_options` and
+/// `Element::click_with_options`.
+#[derive(Debug, Clone)]
+pub struct ClickOptions {
+    pub button: Input::MouseButton,
+    /// 2 for a double click, 3 for a triple click, ...
+    pub click_count: u32,
+    /// Modifier keys held down during the click, e.g. `Ctrl` to open a link in a new tab.
+    pub modifiers: Vec<ModifierKey>,
+    /// Where to click relative to the top-left corner of an element's border box. Clicks the
+    /// element's midpoint if `None`.
+    pub offset: Option<Point>,
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        Self {
+            button: Input::MouseButton::Left,
+            click_count: 1,
+            modifiers: vec![],
+            offset: None,
+        }
+    }
+}
+
+/// What we've told Chrome about the mouse so far, so that consecutive events are consistent.
+#[derive(Debug)]
+struct MouseState {
+    position: Point,
+    /// Bitmask of the buttons currently held down, as used by `Input.dispatchMouseEvent`
+    buttons: u32,
+}
+
+/// How long `Tab::drag` waits for a late `Input.dragIntercepted` event after its last move.
+const DRAG_INTERCEPTION_GRACE: Duration = Duration::from_millis(100);
+
+/// How long `Tab::long_press_point` keeps the finger down.
+const LONG_PRESS_DURATION: Duration = Duration::from_secs(1);
+
+/// Number of intermediate touch moves `Tab::swipe_from_point` dispatches.
+const SWIPE_STEPS: u32 = 10;
+
+/// Copy, link and move, as understood by `Input.DragData::drag_operations_mask`.
+const DRAG_OPERATIONS_ALL: u32 = 1 | 2 | 16;
+
+/// The keyboard layout in use and the modifier keys currently held down with `Tab::key_down`.
+#[derive(Debug, Default)]
+struct KeyboardState {
+    layout: KeyboardLayout,
+    /// Bitmask of the held modifiers, as used by `Input.dispatchKeyEvent`
+    modifiers: u32,
+    alt_gr: bool,
+}
+
+fn mouse_button_mask(button: &Input::MouseButton) -> u32 {
+    match button {
+        Input::MouseButton::None => 0,
+        Input::MouseButton::Left => 1,
+        Input::MouseButton::Right => 2,
+        Input::MouseButton::Middle => 4,
+        Input::MouseButton::Back => 8,
+        Input::MouseButton::Forward => 16,
+    }
+}
+
+fn modifiers_mask(modifiers: Option<&[ModifierKey]>) -> Option<u32> {
+    modifiers.map(|v| v.iter().fold(0, |acc, e| acc | *e as u32))
+}
+
 #[derive(Debug)]
 pub enum RequestPausedDecision {
     Fulfill(FulfillRequest),
@@ -101,6 +206,10 @@ pub type LoadingFailedHandler = Box<
 >;
 
 type SyncSendEvent = dyn EventListener<Event> + Send + Sync;
+
+// Used to give the handlers registered by the `wait_for_*` methods unique names.
+static WAITER_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 pub trait RequestInterceptor {
     fn intercept(
         &self,
@@ -126,6 +235,35 @@ where
 
 type RequestIntercept = dyn RequestInterceptor + Send + Sync;
 
+/// A HAR recording in progress, fed by an event listener.
+struct HarRecording {
+    recorder: Arc<Mutex<HarRecorder>>,
+    listener: Weak<SyncSendEvent>,
+}
+
+/// The log started by `Tab::start_network_log`, fed by an event listener.
+struct NetworkLogging {
+    log: NetworkLog,
+    listener: Weak<SyncSendEvent>,
+}
+
+/// A `Tab::route_from_har` route in update mode, which records the requests that will be added
+/// to the archive once it's removed.
+struct HarUpdate {
+    path: PathBuf,
+    archive: Arc<Har>,
+    options: HarReplayOptions,
+    recording: HarRecording,
+}
+
+/// The arguments of the last `Tab::enable_fetch` call, which are combined with the patterns the
+/// tab's routes need whenever either changes.
+#[derive(Debug, Clone)]
+struct FetchConfig {
+    patterns: Option<Vec<Fetch::RequestPattern>>,
+    handle_auth_requests: Option<bool>,
+}
+
 pub trait EventListener<T> {
     fn on_event(&self, event: &T);
 }
@@ -152,6 +290,19 @@ pub type FunctionBinding = HashMap<String, Arc<SafeBinding>>;
 
 // type SyncSendEvent = dyn EventListener<Event> + Send + Sync;
 
+/// The accessibility roles of the form controls `Tab::find_by_label` looks for.
+const FORM_CONTROL_ROLES: &[&str] = &[
+    "textbox",
+    "searchbox",
+    "combobox",
+    "listbox",
+    "checkbox",
+    "radio",
+    "switch",
+    "slider",
+    "spinbutton",
+];
+
 /// A handle to a single page. Exposes methods for simulating user actions (clicking,
 /// typing), and also for getting information about the DOM and other parts of the page.
 pub struct Tab {
@@ -161,13 +312,26 @@ pub struct Tab {
     navigating: Arc<AtomicBool>,
     target_info: Arc<Mutex<TargetInfo>>,
     request_interceptor: Arc<Mutex<Arc<RequestIntercept>>>,
+    routes: Arc<Mutex<Vec<RegisteredRoute>>>,
+    fetch_config: Arc<Mutex<Option<FetchConfig>>>,
+    har_recording: Arc<Mutex<Option<HarRecording>>>,
+    network_log: Arc<Mutex<Option<NetworkLogging>>>,
+    websocket_mocks: Arc<Mutex<Option<Arc<WebSocketMocks>>>>,
+    har_updates: Arc<Mutex<HashMap<RouteId, HarUpdate>>>,
+    blocked_resource_types: Arc<Mutex<Vec<Network::ResourceType>>>,
+    blocked_request_counts: Arc<Mutex<BlockedRequestCounts>>,
     response_handler: Arc<Mutex<HashMap<String, ResponseHandler>>>,
     loading_failed_handler: Arc<Mutex<HashMap<String, LoadingFailedHandler>>>,
     auth_handler: Arc<Mutex<AuthChallengeResponse>>,
+    /// Answers proxy authentication challenges, taking precedence over `auth_handler`
+    proxy_credentials: Arc<Mutex<Option<AuthChallengeResponse>>>,
     default_timeout: Arc<RwLock<Duration>>,
     page_bindings: Arc<Mutex<FunctionBinding>>,
     event_listeners: Arc<Mutex<Vec<Arc<SyncSendEvent>>>>,
     slow_motion_multiplier: Arc<RwLock<f64>>, // there's no AtomicF64, otherwise would use that
+    mouse_state: Arc<Mutex<MouseState>>,
+    touch_emulation_enabled: Arc<AtomicBool>,
+    keyboard_state: Arc<Mutex<KeyboardState>>,
 }
 
 #[derive(Debug, Error)]
@@ -211,6 +375,35 @@ impl NoElementFound {
     }
 }
 
+/// `scroll_by`/`scroll_to` coordinates can't be infinite or NaN, which have no JS literal.
+fn check_finite_scroll(x: f64, y: f64) -> Result<()> {
+    if x.is_finite() && y.is_finite() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Scroll coordinates must be finite numbers, got ({x}, {y})"
+        ))
+    }
+}
+
+/// Quotes `value` for use as a string literal in an XPath 1.0 expression, which has no escape
+/// sequences; strings containing both kinds of quotes have to be built with `concat()`.
+fn xpath_string_literal(value: &str) -> String {
+    if !value.contains('"') {
+        format!("\"{value}\"")
+    } else if !value.contains('\'') {
+        format!("'{value}'")
+    } else {
+        let parts: Vec<String> = value.split('"').map(|part| format!("\"{part}\"")).collect();
+        format!("concat({})", parts.join(", '\"', "))
+    }
+}
+
+/// Quotes `value` for use as a string in a CSS selector.
+fn css_string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 impl Tab {
     pub fn new(target_info: TargetInfo, transport: Arc<Transport>) -> Result<Self> {
         let target_id = target_info.target_id.clone();
@@ -237,6 +430,14 @@ impl Tab {
             request_interceptor: Arc::new(Mutex::new(Arc::new(
                 |_transport, _session_id, _interception| RequestPausedDecision::Continue(None),
             ))),
+            routes: Arc::new(Mutex::new(Vec::new())),
+            fetch_config: Arc::new(Mutex::new(None)),
+            har_recording: Arc::new(Mutex::new(None)),
+            network_log: Arc::new(Mutex::new(None)),
+            websocket_mocks: Arc::new(Mutex::new(None)),
+            har_updates: Arc::new(Mutex::new(HashMap::new())),
+            blocked_resource_types: Arc::new(Mutex::new(Vec::new())),
+            blocked_request_counts: Arc::new(Mutex::new(BlockedRequestCounts::default())),
             response_handler: Arc::new(Mutex::new(HashMap::new())),
             loading_failed_handler: Arc::new(Mutex::new(HashMap::new())),
             auth_handler: Arc::new(Mutex::new(AuthChallengeResponse {
@@ -244,9 +445,16 @@ impl Tab {
                 username: None,
                 password: None,
             })),
+            proxy_credentials: Arc::new(Mutex::new(None)),
             default_timeout: Arc::new(RwLock::new(Duration::from_secs(20))),
             event_listeners: Arc::new(Mutex::new(Vec::new())),
             slow_motion_multiplier: Arc::new(RwLock::new(0.0)),
+            mouse_state: Arc::new(Mutex::new(MouseState {
+                position: Point { x: 0.0, y: 0.0 },
+                buttons: 0,
+            })),
+            touch_emulation_enabled: Arc::new(AtomicBool::new(false)),
+            keyboard_state: Arc::new(Mutex::new(KeyboardState::default())),
         };
 
         tab.call_method(Page::Enable {
@@ -309,9 +517,13 @@ impl Tab {
             .listen_to_target_events(self.session_id.clone());
         let navigating = Arc::clone(&self.navigating);
         let interceptor_mutex = Arc::clone(&self.request_interceptor);
+        let routes_mutex = Arc::clone(&self.routes);
+        let blocked_resource_types_mutex = Arc::clone(&self.blocked_resource_types);
+        let blocked_request_counts_mutex = Arc::clone(&self.blocked_request_counts);
         let response_handler_mutex = self.response_handler.clone();
         let loading_failed_handler_mutex = self.loading_failed_handler.clone();
         let auth_handler_mutex = self.auth_handler.clone();
+        let proxy_credentials_mutex = Arc::clone(&self.proxy_credentials);
         let session_id = self.session_id.clone();
         let listeners_mutex = Arc::clone(&self.event_listeners);
 
@@ -350,6 +562,33 @@ impl Tab {
                         func.call_binding(json!(payload));
                     }
                     Event::FetchRequestPaused(event) => {
+                        let is_request_stage = event.params.response_status_code.is_none()
+                            && event.params.response_error_reason.is_none();
+                        let is_blocked_type = blocked_resource_types_mutex
+                            .lock()
+                            .unwrap()
+                            .contains(&event.params.resource_Type);
+                        if is_request_stage && is_blocked_type {
+                            blocked_request_counts_mutex
+                                .lock()
+                                .unwrap()
+                                .by_resource_type += 1;
+                            let result = transport.call_method_on_target(
+                                session_id.clone(),
+                                FailRequest {
+                                    request_id: event.params.request_id,
+                                    error_reason: Network::ErrorReason::BlockedByClient,
+                                },
+                            );
+                            if result.is_err() {
+                                warn!("Tried to handle request after connection was closed");
+                            }
+                            continue;
+                        }
+                        let routes = routes_mutex.lock().unwrap().clone();
+                        if route::dispatch(&routes, &event.params, &transport, &session_id) {
+                            continue;
+                        }
                         let interceptor = interceptor_mutex.lock().unwrap();
                         let decision = interceptor.intercept(
                             Arc::clone(&transport),
@@ -390,7 +629,16 @@ impl Tab {
                         }
                     }
                     Event::FetchAuthRequired(event) => {
-                        let auth_challenge_response = auth_handler_mutex.lock().unwrap().clone();
+                        let proxy_credentials = proxy_credentials_mutex.lock().unwrap().clone();
+                        let auth_challenge_response = match proxy_credentials {
+                            Some(credentials)
+                                if event.params.auth_challenge.source
+                                    == Some(Fetch::AuthChallengeSource::Proxy) =>
+                            {
+                                credentials
+                            }
+                            _ => auth_handler_mutex.lock().unwrap().clone(),
+                        };
 
                         let request_id = event.params.request_id;
                         let method = ContinueWithAuth {
@@ -410,18 +658,22 @@ impl Tab {
                             .insert(request_id, ev.params);
                     }
                     Event::NetworkLoadingFinished(ev) => {
+                        // the request is done, so its response needn't be kept any longer
+                        let received = received_event_params
+                            .lock()
+                            .unwrap()
+                            .remove(&ev.params.request_id);
                         response_handler_mutex.lock().unwrap().iter().for_each(
                             |(_name, handler)| {
                                 let request_id = ev.params.request_id.clone();
                                 let retrieve_body = || {
-                                    let method = GetResponseBody {
-                                        request_id: request_id.clone(),
-                                    };
-                                    transport.call_method_on_target(session_id.clone(), method)
+                                    network::get_response_body(
+                                        &transport,
+                                        &session_id,
+                                        request_id.clone(),
+                                    )
                                 };
-                                if let Some(params) =
-                                    received_event_params.lock().unwrap().get(&request_id)
-                                {
+                                if let Some(params) = &received {
                                     handler(params.clone(), &retrieve_body);
                                 } else {
                                     warn!("Request id does not exist");
@@ -429,21 +681,26 @@ impl Tab {
                             },
                         );
                     }
-                    Event::NetworkLoadingFailed(ev) => loading_failed_handler_mutex
-                        .lock()
-                        .unwrap()
-                        .iter()
-                        .for_each(|(_name, handler)| {
-                            let request_id = ev.params.request_id.clone();
-
-                            if let Some(params) =
-                                received_event_params.lock().unwrap().get(&request_id)
-                            {
-                                handler(params.clone(), ev.params.clone());
-                            } else {
-                                warn!("Request id does not exist");
-                            }
-                        }),
+                    Event::NetworkLoadingFailed(ev) => {
+                        if ev.params.blocked_reason == Some(Network::BlockedReason::Inspector) {
+                            blocked_request_counts_mutex.lock().unwrap().by_url += 1;
+                        }
+                        let received = received_event_params
+                            .lock()
+                            .unwrap()
+                            .remove(&ev.params.request_id);
+                        loading_failed_handler_mutex
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .for_each(|(_name, handler)| {
+                                if let Some(params) = &received {
+                                    handler(params.clone(), ev.params.clone());
+                                } else {
+                                    warn!("Request id does not exist");
+                                }
+                            });
+                    }
                     _ => {
                         let raw_event = format!("{event:?}");
                         trace!(
@@ -728,6 +985,48 @@ impl Tab {
         self.run_query_selector_on_node(root_node_id, selector)
     }
 
+    /// Like `find_element`, but returns an owned `ElementHandle` which can outlive the borrow of
+    /// this tab, e.g. to store it or to send it to another thread.
+    pub fn find_element_handle(self: &Arc<Self>, selector: &str) -> Result<ElementHandle> {
+        self.find_element(selector)?.to_handle(self)
+    }
+
+    /// Like `wait_for_element`, but returns an owned `ElementHandle`.
+    pub fn wait_for_element_handle(self: &Arc<Self>, selector: &str) -> Result<ElementHandle> {
+        self.wait_for_element(selector)?.to_handle(self)
+    }
+
+    /// Like `find_element`, but also looks inside open shadow roots.
+    ///
+    /// The selector is matched against the document and, recursively, against every open shadow
+    /// root in it. `>>>` can be used to chain selectors: `"my-app >>> form >>> input"` finds
+    /// inputs anywhere inside (the shadow trees of) forms anywhere inside `my-app`. Elements in
+    /// closed shadow roots can't be reached.
+    ///
+    /// The returned elements can be used like any other, e.g. with `click` and `type_into`.
+    pub fn find_element_deep(&self, selector: &str) -> Result<Element<'_>> {
+        self.find_elements_deep(selector)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| NoElementFound {}.into())
+    }
+
+    /// Like `find_elements`, but also looks inside open shadow roots.
+    /// See `find_element_deep` for details.
+    pub fn find_elements_deep(&self, selector: &str) -> Result<Vec<Element<'_>>> {
+        trace!("Looking up elements via deep selector: {selector}");
+        let document = Element::new(self, self.get_document()?.node_id)?;
+        document.find_elements_deep(selector)
+    }
+
+    /// Creates a [`Locator`] for the given CSS selector.
+    ///
+    /// Nothing is looked up until an action is performed on the locator, and the lookup is
+    /// repeated for every action, so it keeps working when the page re-renders the element.
+    pub fn locator(&self, selector: &str) -> Locator<'_> {
+        Locator::new(self, selector)
+    }
+
     pub fn find_element_by_xpath(&self, query: &str) -> Result<Element<'_>> {
         self.get_document()?;
 
@@ -789,6 +1088,95 @@ impl Tab {
             .collect()
     }
 
+    /// Turns a JS array of DOM nodes (e.g. the result of `Element::call_js_fn`) into `Element`s,
+    /// releasing the array afterwards.
+    pub(crate) fn elements_from_js_array(
+        &self,
+        array: Runtime::RemoteObject,
+    ) -> Result<Vec<Element<'_>>> {
+        if array.subtype != Some(Runtime::RemoteObjectSubtype::Array) {
+            return Err(anyhow::anyhow!(
+                "expected an array of elements, got: {}",
+                array.description.unwrap_or_default()
+            ));
+        }
+        let array_object_id = array
+            .object_id
+            .ok_or_else(|| anyhow::anyhow!("expected an array of elements by reference"))?;
+
+        let elements = self.elements_from_js_array_properties(&array_object_id);
+        let released = self.call_method(Runtime::ReleaseObject {
+            object_id: array_object_id,
+        });
+        let elements = elements?;
+        released?;
+        Ok(elements)
+    }
+
+    fn elements_from_js_array_properties(&self, array_object_id: &str) -> Result<Vec<Element<'_>>> {
+        let properties = self
+            .call_method(Runtime::GetProperties {
+                object_id: array_object_id.to_string(),
+                own_properties: Some(true),
+                accessor_properties_only: None,
+                generate_preview: None,
+                non_indexed_properties_only: None,
+            })?
+            .result;
+
+        let mut elements = vec![];
+        for property in properties {
+            if property.name.parse::<usize>().is_err() {
+                continue;
+            }
+            if let Some(object_id) = property.value.and_then(|value| value.object_id) {
+                let node_id = self.call_method(DOM::RequestNode { object_id })?.node_id;
+                elements.push(Element::new(self, node_id)?);
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Turns a JS object referring to a DOM node into an `Element`, or `None` for `null` and
+    /// `undefined`.
+    pub(crate) fn element_from_js_object(
+        &self,
+        object: Runtime::RemoteObject,
+    ) -> Result<Option<Element<'_>>> {
+        let Some(object_id) = object.object_id else {
+            return Ok(None);
+        };
+        if object.subtype != Some(Runtime::RemoteObjectSubtype::Node) {
+            self.call_method(Runtime::ReleaseObject {
+                object_id: object_id.clone(),
+            })?;
+            return Err(anyhow::anyhow!(
+                "expected a DOM node, got: {}",
+                object.description.unwrap_or_default()
+            ));
+        }
+
+        let node_id = self.call_method(DOM::RequestNode { object_id })?.node_id;
+        Ok(Some(Element::new(self, node_id)?))
+    }
+
+    /// Creates an `Element` for the node with this backend node ID, e.g. one reported by
+    /// `DOM.describeNode`.
+    pub(crate) fn element_from_backend_node_id(
+        &self,
+        backend_node_id: DOM::BackendNodeId,
+    ) -> Result<Element<'_>> {
+        let node_id = self
+            .call_method(DOM::PushNodesByBackendIdsToFrontend {
+                backend_node_ids: vec![backend_node_id],
+            })?
+            .node_ids
+            .into_iter()
+            .find(|node_id| *node_id != 0)
+            .ok_or(NoElementFound {})?;
+        Element::new(self, node_id)
+    }
+
     pub fn get_document(&self) -> Result<Node> {
         Ok(self
             .call_method(DOM::GetDocument {
@@ -859,6 +1247,155 @@ impl Tab {
         })
     }
 
+    /// Returns the first element with a text node child containing `text`.
+    ///
+    /// Whitespace in the page's text is normalized before comparing, as it would be when
+    /// rendered.
+    pub fn find_by_text(&self, text: &str) -> Result<Element<'_>> {
+        self.find_element_by_xpath(&format!(
+            "//*[text()[contains(normalize-space(.), {})]]",
+            xpath_string_literal(text)
+        ))
+    }
+
+    /// Returns the first element with the given ARIA `role` (e.g. `"button"`, `"link"`,
+    /// `"checkbox"`) and, if given, exactly the given accessible `name`.
+    ///
+    /// Roles and names are computed by Chrome's accessibility tree, so this matches what
+    /// assistive technology sees, including implicit roles (`<button>` is a `"button"`) and names
+    /// derived from labels, `aria-label`, `aria-labelledby` or text content.
+    pub fn find_by_role(&self, role: &str, name: Option<&str>) -> Result<Element<'_>> {
+        self.query_accessibility_tree(Some(role), name, None)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| NoElementFound {}.into())
+    }
+
+    /// Returns the first form control whose accessible name is `label`, as given by an associated
+    /// `<label>`, `aria-label` or `aria-labelledby`.
+    pub fn find_by_label(&self, label: &str) -> Result<Element<'_>> {
+        self.query_accessibility_tree(None, Some(label), Some(FORM_CONTROL_ROLES))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| NoElementFound {}.into())
+    }
+
+    /// Returns the first element whose `placeholder` attribute is exactly `placeholder`.
+    pub fn find_by_placeholder(&self, placeholder: &str) -> Result<Element<'_>> {
+        self.find_element(&format!(
+            "[placeholder={}]",
+            css_string_literal(placeholder)
+        ))
+    }
+
+    /// Returns the first element whose `data-testid` attribute is exactly `test_id`.
+    pub fn find_by_test_id(&self, test_id: &str) -> Result<Element<'_>> {
+        self.find_element(&format!("[data-testid={}]", css_string_literal(test_id)))
+    }
+
+    /// Queries the accessibility tree, keeping only nodes with one of `allowed_roles` if given.
+    fn query_accessibility_tree(
+        &self,
+        role: Option<&str>,
+        accessible_name: Option<&str>,
+        allowed_roles: Option<&[&str]>,
+    ) -> Result<Vec<Element<'_>>> {
+        let root_node_id = self.get_document()?.node_id;
+        let nodes = self
+            .call_method(Accessibility::QueryAXTree {
+                node_id: Some(root_node_id),
+                backend_node_id: None,
+                object_id: None,
+                accessible_name: accessible_name.map(ToString::to_string),
+                role: role.map(ToString::to_string),
+            })?
+            .nodes;
+
+        let backend_node_ids: Vec<DOM::BackendNodeId> = nodes
+            .into_iter()
+            .filter(|node| !node.ignored)
+            // Text runs share the accessible name of the text they contain, but they aren't
+            // what anybody is looking for when searching by name
+            .filter(|node| {
+                let node_role = node
+                    .role
+                    .as_ref()
+                    .and_then(|role| role.value.as_ref())
+                    .and_then(|role| role.as_str());
+                !matches!(
+                    node_role,
+                    Some("StaticText" | "InlineTextBox" | "LabelText")
+                ) && allowed_roles.is_none_or(|allowed| {
+                    node_role.is_some_and(|node_role| allowed.contains(&node_role))
+                })
+            })
+            .filter_map(|node| node.backend_dom_node_id)
+            .collect();
+
+        if backend_node_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.call_method(DOM::PushNodesByBackendIdsToFrontend { backend_node_ids })?
+            .node_ids
+            .into_iter()
+            .filter(|node_id| *node_id != 0)
+            .map(|node_id| Element::new(self, node_id))
+            .collect()
+    }
+
+    /// Fills in several form fields at once, `fields` being pairs of a CSS selector and a
+    /// value (see `FormValue`), like a `HashMap<&str, FormValue>`. Fields are filled in the
+    /// iteration order of `fields`.
+    ///
+    /// All fields are attempted, even if some fail; the failures are then reported together as
+    /// a `FillFormError`.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// use headless_chrome::Browser;
+    /// use headless_chrome::browser::tab::element::FormValue;
+    /// let browser = Browser::default()?;
+    /// let tab = browser.new_tab()?;
+    /// tab.navigate_to("https://example.com/signup")?.wait_until_navigated()?;
+    ///
+    /// tab.fill_form([
+    ///     ("#name", FormValue::from("Ada")),
+    ///     ("#country", "GB".into()),
+    ///     ("#newsletter", true.into()),
+    /// ])?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fill_form<I, S, V>(&self, fields: I) -> Result<&Self>
+    where
+        I: IntoIterator<Item = (S, V)>,
+        S: AsRef<str>,
+        V: Into<FormValue>,
+    {
+        let mut failures = vec![];
+        for (selector, value) in fields {
+            let selector = selector.as_ref();
+            let value = value.into();
+            if let Err(error) = self
+                .find_element(selector)
+                .and_then(|element| element.fill_value(&value).map(|_| ()))
+            {
+                debug!("Couldn't fill {selector}: {error}");
+                failures.push((selector.to_string(), error));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(self)
+        } else {
+            Err(FillFormError { failures }.into())
+        }
+    }
+
     pub fn describe_node(&self, node_id: NodeId) -> Result<Node> {
         let node = self
             .call_method(DOM::DescribeNode {
@@ -872,32 +1409,53 @@ impl Tab {
         Ok(node)
     }
 
+    /// Types `string_to_type` key by key on the current keyboard layout (see
+    /// `set_keyboard_layout`). Newlines press Enter and tabs press Tab.
     pub fn type_str(&self, string_to_type: &str) -> Result<&Self> {
-        for c in string_to_type.split("") {
-            // split call above will have empty string at start and end which we won't type
-            if c.is_empty() {
-                continue;
-            }
-            let definition = keys::get_key_definition(c);
-            // https://github.com/puppeteer/puppeteer/blob/b8806d5625ca7835abbaf2e997b0bf35a5679e29/src/common/Input.ts#L239-L245
-            match definition {
-                Ok(key) => {
-                    let v: DispatchKeyEvent = key.into();
-
-                    self.call_method(v.clone())?;
-                    self.call_method(DispatchKeyEvent {
-                        Type: Input::DispatchKeyEventTypeOption::KeyUp,
-                        ..v
-                    })?;
-                }
-                Err(_) => {
-                    self.send_character(c)?;
-                }
-            }
+        let mut buffer = [0; 4];
+        for c in string_to_type.chars() {
+            let c: &str = match c {
+                '\n' | '\r' => "Enter",
+                '\t' => "Tab",
+                c => c.encode_utf8(&mut buffer),
+            };
+            self.type_character(c)?;
         }
         Ok(self)
     }
 
+    /// Types `c` as it would be on the current keyboard layout, holding Shift or `AltGr` if
+    /// that's what the layout needs. Characters the layout doesn't have are inserted without
+    /// any key events.
+    fn type_character(&self, c: &str) -> Result<()> {
+        // https://github.com/puppeteer/puppeteer/blob/b8806d5625ca7835abbaf2e997b0bf35a5679e29/src/common/Input.ts#L239-L245
+        let (level, shift_held) = {
+            let state = self.keyboard_state.lock().unwrap();
+            let level = state.layout.find_character(c).map(|(_, level)| level);
+            (level, state.modifiers & keys::modifier_bit("Shift") != 0)
+        };
+        let modifier = match level {
+            Some(keys::KeyLevel::Shifted) if !shift_held => Some("Shift"),
+            Some(keys::KeyLevel::AltGr) => Some("AltGraph"),
+            Some(_) => None,
+            None if keys::get_key_definition(c).is_ok() => None,
+            None => {
+                self.send_character(c)?;
+                return Ok(());
+            }
+        };
+
+        if let Some(modifier) = modifier {
+            self.key_down(modifier)?;
+        }
+        self.key_down(c)?;
+        self.key_up(c)?;
+        if let Some(modifier) = modifier {
+            self.key_up(modifier)?;
+        }
+        Ok(())
+    }
+
     /// Does the same as `type_str` but it only dispatches a `keypress` and `input` event.
     /// It does not send a `keydown` or `keyup` event.
     ///
@@ -910,88 +1468,141 @@ impl Tab {
         Ok(self)
     }
 
-    /// Press a key on the keyboard, optionally with some modifier keys.
-    /// See [this file](https://github.com/puppeteer/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/USKeyboardLayout.js)
-    /// for a full definition of which strings correspond with which
-    /// keys.
-    pub fn press_key_with_modifiers(
-        &self,
-        key: &str,
-        modifiers: Option<&[ModifierKey]>,
-    ) -> Result<&Self> {
-        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L114-L115
-        let definition = keys::get_key_definition(key)?;
+    /// Sets the keyboard layout `type_str` and the other keyboard methods use. Defaults to US.
+    pub fn set_keyboard_layout(&self, layout: KeyboardLayout) -> &Self {
+        self.keyboard_state.lock().unwrap().layout = layout;
+        self
+    }
 
-        let text = definition
-            .text
-            .or({
-                if definition.key.len() == 1 {
-                    Some(definition.key)
-                } else {
-                    None
-                }
-            })
-            .map(std::string::ToString::to_string);
+    /// Presses `key` and keeps it held down until `key_up`.
+    ///
+    /// `key` can be a character (`"a"`), a key name (`"Enter"`, `"Shift"`) or a code
+    /// (`"KeyA"`, `"ShiftRight"`); codes type whatever the held modifiers make them type on the
+    /// current keyboard layout. While a modifier key is held, it applies to all other key and
+    /// mouse events, and keys pressed with Control, Alt or Meta held don't type any text.
+    pub fn key_down(&self, key: &str) -> Result<&Self> {
+        self.dispatch_key(key, true)
+    }
 
-        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L52
-        let key_down_event_type = if text.is_some() {
-            Input::DispatchKeyEventTypeOption::KeyDown
+    /// Releases `key` (see `key_down`).
+    pub fn key_up(&self, key: &str) -> Result<&Self> {
+        self.dispatch_key(key, false)
+    }
+
+    fn dispatch_key(&self, key: &str, down: bool) -> Result<&Self> {
+        let mut state = self.keyboard_state.lock().unwrap();
+        let shift = keys::modifier_bit("Shift");
+        let description = keys::describe_key(
+            &state.layout,
+            key,
+            state.modifiers & shift != 0,
+            state.alt_gr,
+        )?;
+
+        let modifier = keys::modifier_bit(&description.key);
+        let alt_gr = description.key == "AltGraph";
+        let (modifiers, alt_gr) = if down {
+            (state.modifiers | modifier, state.alt_gr || alt_gr)
         } else {
-            Input::DispatchKeyEventTypeOption::RawKeyDown
+            (state.modifiers & !modifier, state.alt_gr && !alt_gr)
         };
 
-        let key = Some(definition.key.to_string());
-        let code = Some(definition.code.to_string());
+        // Shortcuts don't type anything, e.g. Control+A selects everything
+        let text = description
+            .text
+            .filter(|_| down && modifiers & !shift == 0 && modifier == 0);
 
-        let modifiers = modifiers.map(|v| v.iter().fold(0, |acc, e| acc | *e as u32));
+        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L52
+        let event_type = match (down, &text) {
+            (false, _) => Input::DispatchKeyEventTypeOption::KeyUp,
+            (true, Some(_)) => Input::DispatchKeyEventTypeOption::KeyDown,
+            (true, None) => Input::DispatchKeyEventTypeOption::RawKeyDown,
+        };
 
         self.optional_slow_motion_sleep(25);
 
         self.call_method(Input::DispatchKeyEvent {
-            Type: key_down_event_type,
-            key: key.clone(),
+            Type: event_type,
+            key: Some(description.key),
             text: text.clone(),
-            code: code.clone(),
-            windows_virtual_key_code: Some(definition.key_code),
-            native_virtual_key_code: Some(definition.key_code),
-            modifiers,
-            timestamp: None,
-            unmodified_text: None,
-            key_identifier: None,
-            auto_repeat: None,
-            is_keypad: None,
-            is_system_key: None,
-            location: None,
-            commands: None,
-        })?;
-        self.call_method(Input::DispatchKeyEvent {
-            Type: Input::DispatchKeyEventTypeOption::KeyUp,
-            key,
-            text,
-            code,
-            windows_virtual_key_code: Some(definition.key_code),
-            native_virtual_key_code: Some(definition.key_code),
-            modifiers,
+            code: Some(description.code),
+            windows_virtual_key_code: Some(description.key_code),
+            native_virtual_key_code: Some(description.key_code),
+            modifiers: Some(modifiers),
             timestamp: None,
-            unmodified_text: None,
+            unmodified_text: text,
             key_identifier: None,
             auto_repeat: None,
-            is_keypad: None,
+            is_keypad: Some(description.location == Some(3)),
             is_system_key: None,
-            location: None,
+            location: description.location,
             commands: None,
         })?;
+        state.modifiers = modifiers;
+        state.alt_gr = alt_gr;
+
+        Ok(self)
+    }
+
+    /// Press a key on the keyboard, optionally with some modifier keys.
+    ///
+    /// The modifiers are pressed before and released after the key; see `press_key`.
+    pub fn press_key_with_modifiers(
+        &self,
+        key: &str,
+        modifiers: Option<&[ModifierKey]>,
+    ) -> Result<&Self> {
+        let modifiers: Vec<&str> = modifiers
+            .unwrap_or_default()
+            .iter()
+            .map(|modifier| modifier.key_name())
+            .collect();
+        self.with_keys_held(&modifiers, || self.press_key(key).map(|_| ()))?;
         Ok(self)
     }
 
     /// Press a key on the keyboard. See [this file](https://github.com/puppeteer/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/USKeyboardLayout.js)
     /// for a full definition of which strings correspond with which
-    /// keys.
+    /// keys; codes like `KeyA` work too (see `key_down`).
+    ///
+    /// `key` may also be a chord like `"Control+Shift+KeyK"`: its keys are pressed in order and
+    /// released in reverse order.
     pub fn press_key(&self, key: &str) -> Result<&Self> {
-        self.press_key_with_modifiers(key, None)
+        let chord = keys::split_chord(key);
+        let chord: Vec<&str> = chord.iter().map(String::as_str).collect();
+        self.with_keys_held(&chord, || Ok(()))?;
+        Ok(self)
+    }
+
+    /// Presses `keys` in order, runs `action`, and releases them in reverse order. The keys
+    /// which were pressed are released even if pressing another key or `action` fails, so that
+    /// no modifier stays held for later input.
+    fn with_keys_held<T>(&self, keys: &[&str], action: impl FnOnce() -> Result<T>) -> Result<T> {
+        let mut pressed = 0;
+        let mut result = Ok(());
+        for key in keys {
+            result = self.key_down(key).map(|_| ());
+            if result.is_err() {
+                break;
+            }
+            pressed += 1;
+        }
+        let result = result.and_then(|()| action());
+
+        let mut released = Ok(());
+        for key in keys[..pressed].iter().rev() {
+            if let Err(error) = self.key_up(key) {
+                released = released.and(Err(error));
+            }
+        }
+        let value = result?;
+        released?;
+        Ok(value)
     }
 
     /// Moves the mouse to this point (dispatches a mouseMoved event)
+    ///
+    /// Any buttons held down with `mouse_down` stay pressed while moving.
     pub fn move_mouse_to_point(&self, point: Point) -> Result<&Self> {
         if point.x == 0.0 && point.y == 0.0 {
             warn!("Midpoint of element shouldn't be 0,0. Something is probably wrong.");
@@ -999,6 +1610,7 @@ impl Tab {
 
         self.optional_slow_motion_sleep(100);
 
+        let buttons = self.mouse_state.lock().unwrap().buttons;
         self.call_method(Input::DispatchMouseEvent {
             Type: Input::DispatchMouseEventTypeOption::MouseMoved,
             x: point.x,
@@ -1006,7 +1618,7 @@ impl Tab {
             modifiers: None,
             timestamp: None,
             button: None,
-            buttons: None,
+            buttons: Some(buttons),
             click_count: None,
             force: None,
             tangential_pressure: None,
@@ -1017,28 +1629,65 @@ impl Tab {
             delta_y: None,
             pointer_Type: None,
         })?;
+        self.mouse_state.lock().unwrap().position = point;
 
         Ok(self)
     }
 
-    pub fn click_point(&self, point: Point) -> Result<&Self> {
-        trace!("Clicking point: {point:?}");
-        if point.x == 0.0 && point.y == 0.0 {
-            warn!("Midpoint of element shouldn't be 0,0. Something is probably wrong.");
-        }
+    /// Presses `button` at the current mouse position and keeps it held down until `mouse_up`.
+    pub fn mouse_down(
+        &self,
+        button: Input::MouseButton,
+        modifiers: Option<&[ModifierKey]>,
+    ) -> Result<&Self> {
+        self.dispatch_mouse_button(
+            Input::DispatchMouseEventTypeOption::MousePressed,
+            button,
+            1,
+            modifiers,
+        )
+    }
 
-        self.move_mouse_to_point(point)?;
+    /// Releases `button` (see `mouse_down`) at the current mouse position.
+    pub fn mouse_up(
+        &self,
+        button: Input::MouseButton,
+        modifiers: Option<&[ModifierKey]>,
+    ) -> Result<&Self> {
+        self.dispatch_mouse_button(
+            Input::DispatchMouseEventTypeOption::MouseReleased,
+            button,
+            1,
+            modifiers,
+        )
+    }
+
+    fn dispatch_mouse_button(
+        &self,
+        event_type: Input::DispatchMouseEventTypeOption,
+        button: Input::MouseButton,
+        click_count: u32,
+        modifiers: Option<&[ModifierKey]>,
+    ) -> Result<&Self> {
+        let mut state = self.mouse_state.lock().unwrap();
+        let buttons = match event_type {
+            Input::DispatchMouseEventTypeOption::MousePressed => {
+                state.buttons | mouse_button_mask(&button)
+            }
+            _ => state.buttons & !mouse_button_mask(&button),
+        };
+        let point = state.position;
+        let held_modifiers = self.keyboard_state.lock().unwrap().modifiers;
 
-        self.optional_slow_motion_sleep(250);
         self.call_method(Input::DispatchMouseEvent {
-            Type: Input::DispatchMouseEventTypeOption::MousePressed,
+            Type: event_type,
             x: point.x,
             y: point.y,
-            button: Some(Input::MouseButton::Left),
-            click_count: Some(1),
-            modifiers: None,
+            button: Some(button),
+            click_count: Some(click_count),
+            modifiers: Some(modifiers_mask(modifiers).unwrap_or(0) | held_modifiers),
             timestamp: None,
-            buttons: None,
+            buttons: Some(buttons),
             force: None,
             tangential_pressure: None,
             tilt_x: None,
@@ -1048,27 +1697,483 @@ impl Tab {
             delta_y: None,
             pointer_Type: None,
         })?;
+        state.buttons = buttons;
+
+        Ok(self)
+    }
+
+    pub fn click_point(&self, point: Point) -> Result<&Self> {
+        self.click_point_with_options(point, &ClickOptions::default())
+    }
+
+    /// Double-clicks this point with the left mouse button.
+    pub fn double_click_point(&self, point: Point) -> Result<&Self> {
+        self.click_point_with_options(
+            point,
+            &ClickOptions {
+                click_count: 2,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Clicks this point with the right mouse button, which usually opens a context menu.
+    pub fn right_click_point(&self, point: Point) -> Result<&Self> {
+        self.click_point_with_options(
+            point,
+            &ClickOptions {
+                button: Input::MouseButton::Right,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Clicks this point with the middle mouse button.
+    pub fn middle_click_point(&self, point: Point) -> Result<&Self> {
+        self.click_point_with_options(
+            point,
+            &ClickOptions {
+                button: Input::MouseButton::Middle,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Moves the mouse to `point` and clicks it as described by `options`.
+    ///
+    /// `options.offset` is ignored here, as `point` is already absolute; see
+    /// `Element::click_with_options`.
+    ///
+    /// For a click count of `n`, `n` press/release pairs are dispatched with increasing click
+    /// counts, just like a real double (or triple) click.
+    pub fn click_point_with_options(&self, point: Point, options: &ClickOptions) -> Result<&Self> {
+        trace!("Clicking point: {point:?} with {options:?}");
+        if point.x == 0.0 && point.y == 0.0 {
+            warn!("Midpoint of element shouldn't be 0,0. Something is probably wrong.");
+        }
+
+        self.move_mouse_to_point(point)?;
+
+        self.optional_slow_motion_sleep(250);
+        let modifiers = Some(options.modifiers.as_slice());
+        for click_count in 1..=options.click_count.max(1) {
+            self.dispatch_mouse_button(
+                Input::DispatchMouseEventTypeOption::MousePressed,
+                options.button.clone(),
+                click_count,
+                modifiers,
+            )?;
+            self.dispatch_mouse_button(
+                Input::DispatchMouseEventTypeOption::MouseReleased,
+                options.button.clone(),
+                click_count,
+                modifiers,
+            )?;
+        }
+        Ok(self)
+    }
+
+    /// Drags with the left mouse button from `from` to `to`, moving the mouse there in `steps`
+    /// equal increments.
+    ///
+    /// Pages that implement dragging with plain mouse events (like most sortable lists) see a
+    /// press, a series of moves and a release. If the press starts an HTML5 drag-and-drop
+    /// operation instead, the drag is intercepted (via `Input.setInterceptDrags`) and replayed
+    /// with `Input.dispatchDragEvent`, so that `dragenter`, `dragover` and `drop` fire with the
+    /// `dataTransfer` payload the page set up in `dragstart`.
+    pub fn drag(&self, from: Point, to: Point, steps: u32) -> Result<&Self> {
+        trace!("Dragging from {from:?} to {to:?} in {steps} steps");
+
+        let (tx, rx) = mpsc::channel();
+        let listener = self.add_event_listener(Arc::new(move |event: &Event| {
+            if let Event::InputDragIntercepted(ev) = event {
+                tx.send(ev.params.data.clone()).ok();
+            }
+        }))?;
+        self.call_method(Input::SetInterceptDrags { enabled: true })?;
+
+        let result = self.drag_with_interception(from, to, steps, &rx);
+
+        // clean up even if the drag failed, and report the drag's error first
+        let stopped_intercepting = self.call_method(Input::SetInterceptDrags { enabled: false });
+        let removed_listener = self.remove_event_listener(&listener);
+        result?;
+        stopped_intercepting?;
+        removed_listener?;
+
+        Ok(self)
+    }
+
+    fn drag_with_interception(
+        &self,
+        from: Point,
+        to: Point,
+        steps: u32,
+        intercepted: &mpsc::Receiver<Input::DragData>,
+    ) -> Result<()> {
+        self.move_mouse_to_point(from)?;
+        self.mouse_down(Input::MouseButton::Left, None)?;
+
+        // the button must be released whatever happens, or later mouse moves would be drags
+        let result = self.drag_steps(from, to, steps, intercepted);
+        let released = self.mouse_up(Input::MouseButton::Left, None);
+        if released.is_err() {
+            self.mouse_state.lock().unwrap().buttons &=
+                !mouse_button_mask(&Input::MouseButton::Left);
+        }
+        result?;
+        released?;
+        Ok(())
+    }
+
+    fn drag_steps(
+        &self,
+        from: Point,
+        to: Point,
+        steps: u32,
+        intercepted: &mpsc::Receiver<Input::DragData>,
+    ) -> Result<()> {
+        use Input::DispatchDragEventTypeOption::{DragEnter, DragOver, Drop};
+
+        let steps = steps.max(1);
+        let mut drag_data: Option<Input::DragData> = None;
+        for step in 1..=steps {
+            let point = from + (to - from) * (f64::from(step) / f64::from(steps));
+            if let Some(data) = &drag_data {
+                self.dispatch_drag_event(DragOver, point, data)?;
+                continue;
+            }
+
+            self.move_mouse_to_point(point)?;
+            drag_data = intercepted.try_recv().ok();
+            if let Some(data) = &drag_data {
+                self.dispatch_drag_event(DragEnter, point, data)?;
+                self.dispatch_drag_event(DragOver, point, data)?;
+            }
+        }
+
+        if drag_data.is_none() {
+            // The event handler thread may not have seen the interception by the time the mouse
+            // move that started the drag returned.
+            drag_data = intercepted.recv_timeout(DRAG_INTERCEPTION_GRACE).ok();
+            if let Some(data) = &drag_data {
+                self.dispatch_drag_event(DragEnter, to, data)?;
+                self.dispatch_drag_event(DragOver, to, data)?;
+            }
+        }
+
+        if let Some(data) = &drag_data {
+            self.dispatch_drag_event(Drop, to, data)?;
+        }
+        Ok(())
+    }
+
+    /// Drops files from disk onto `point`, as if they had been dragged there from a file
+    /// manager. The paths should be absolute.
+    pub fn drop_files_at_point(&self, point: Point, files: &[&str]) -> Result<&Self> {
+        use Input::DispatchDragEventTypeOption::{DragEnter, DragOver, Drop};
+
+        debug!("Dropping files {files:?} at {point:?}");
+        let data = Input::DragData {
+            items: vec![],
+            files: Some(files.iter().map(ToString::to_string).collect()),
+            drag_operations_mask: DRAG_OPERATIONS_ALL,
+        };
+
+        self.move_mouse_to_point(point)?;
+        self.dispatch_drag_event(DragEnter, point, &data)?;
+        self.dispatch_drag_event(DragOver, point, &data)?;
+        self.dispatch_drag_event(Drop, point, &data)?;
+
+        Ok(self)
+    }
+
+    fn dispatch_drag_event(
+        &self,
+        event_type: Input::DispatchDragEventTypeOption,
+        point: Point,
+        data: &Input::DragData,
+    ) -> Result<()> {
+        self.optional_slow_motion_sleep(50);
+        self.call_method(Input::DispatchDragEvent {
+            Type: event_type,
+            x: point.x,
+            y: point.y,
+            data: data.clone(),
+            modifiers: None,
+        })?;
+        self.mouse_state.lock().unwrap().position = point;
+        Ok(())
+    }
+
+    /// Makes the page report touch support (`navigator.maxTouchPoints`, `ontouchstart`, touch
+    /// media queries, ...), which Chrome requires before it accepts touch input.
+    ///
+    /// The touch methods (`tap_point`, `swipe_from_point`, ...) call this automatically.
+    pub fn enable_touch_emulation(&self) -> Result<&Self> {
+        if !self.touch_emulation_enabled.swap(true, Ordering::SeqCst) {
+            let result = self.call_method(Emulation::SetTouchEmulationEnabled {
+                enabled: true,
+                max_touch_points: Some(5),
+            });
+            if let Err(error) = result {
+                self.touch_emulation_enabled.store(false, Ordering::SeqCst);
+                return Err(error);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn disable_touch_emulation(&self) -> Result<&Self> {
+        self.call_method(Emulation::SetTouchEmulationEnabled {
+            enabled: false,
+            max_touch_points: None,
+        })?;
+        self.touch_emulation_enabled.store(false, Ordering::SeqCst);
+        Ok(self)
+    }
+
+    /// Taps this point with one finger.
+    pub fn tap_point(&self, point: Point) -> Result<&Self> {
+        self.synthesize_tap(point, None)
+    }
+
+    /// Touches this point and holds the finger down long enough for a long press, which
+    /// usually opens a context menu.
+    pub fn long_press_point(&self, point: Point) -> Result<&Self> {
+        self.synthesize_tap(point, Some(LONG_PRESS_DURATION))
+    }
+
+    fn synthesize_tap(&self, point: Point, duration: Option<Duration>) -> Result<&Self> {
+        trace!("Tapping point: {point:?} for {duration:?}");
+        self.enable_touch_emulation()?;
+        self.optional_slow_motion_sleep(250);
+        self.call_method(Input::SynthesizeTapGesture {
+            x: point.x,
+            y: point.y,
+            duration: duration.map(|d| u32::try_from(d.as_millis()).unwrap_or(u32::MAX)),
+            tap_count: None,
+            gesture_source_Type: Some(Input::GestureSourceType::Touch),
+        })?;
+        Ok(self)
+    }
+
+    /// Puts a finger down on `point` and slides it `distance` CSS pixels towards `direction`
+    /// before lifting it, dispatching `touchstart`, a series of `touchmove`s and `touchend`.
+    ///
+    /// Note that swiping up scrolls the page down, as on a phone.
+    pub fn swipe_from_point(
+        &self,
+        point: Point,
+        direction: SwipeDirection,
+        distance: f64,
+    ) -> Result<&Self> {
+        trace!("Swiping {direction:?} by {distance} from {point:?}");
+        self.enable_touch_emulation()?;
+
+        let delta = match direction {
+            SwipeDirection::Up => Point {
+                x: 0.0,
+                y: -distance,
+            },
+            SwipeDirection::Down => Point {
+                x: 0.0,
+                y: distance,
+            },
+            SwipeDirection::Left => Point {
+                x: -distance,
+                y: 0.0,
+            },
+            SwipeDirection::Right => Point {
+                x: distance,
+                y: 0.0,
+            },
+        };
+
+        self.dispatch_touch_event(Input::DispatchTouchEventTypeOption::TouchStart, Some(point))?;
+        for step in 1..=SWIPE_STEPS {
+            self.optional_slow_motion_sleep(20);
+            let current = point + delta * (f64::from(step) / f64::from(SWIPE_STEPS));
+            self.dispatch_touch_event(
+                Input::DispatchTouchEventTypeOption::TouchMove,
+                Some(current),
+            )?;
+        }
+        self.dispatch_touch_event(Input::DispatchTouchEventTypeOption::TouchEnd, None)?;
+
+        Ok(self)
+    }
+
+    /// Pinches with two fingers centered on `point`. A `scale` above 1.0 zooms in, below 1.0
+    /// zooms out.
+    pub fn pinch_at_point(&self, point: Point, scale: f64) -> Result<&Self> {
+        trace!("Pinching {point:?} by {scale}");
+        self.enable_touch_emulation()?;
+        self.call_method(Input::SynthesizePinchGesture {
+            x: point.x,
+            y: point.y,
+            scale_factor: scale,
+            relative_speed: None,
+            gesture_source_Type: Some(Input::GestureSourceType::Touch),
+        })?;
+        Ok(self)
+    }
+
+    /// Touch events describe every finger still on the screen, so ending the (only) touch
+    /// means sending no touch points at all.
+    fn dispatch_touch_event(
+        &self,
+        event_type: Input::DispatchTouchEventTypeOption,
+        point: Option<Point>,
+    ) -> Result<()> {
+        let touch_points = point
+            .map(|point| Input::TouchPoint {
+                x: point.x,
+                y: point.y,
+                radius_x: None,
+                radius_y: None,
+                rotation_angle: None,
+                force: None,
+                tangential_pressure: None,
+                tilt_x: None,
+                tilt_y: None,
+                twist: None,
+                id: None,
+            })
+            .into_iter()
+            .collect();
+
+        self.call_method(Input::DispatchTouchEvent {
+            Type: event_type,
+            touch_points,
+            modifiers: None,
+            timestamp: None,
+        })?;
+        Ok(())
+    }
+
+    /// Scrolls the page by `delta_x`/`delta_y` CSS pixels.
+    pub fn scroll_by(&self, delta_x: f64, delta_y: f64) -> Result<&Self> {
+        check_finite_scroll(delta_x, delta_y)?;
+        self.evaluate(
+            &format!("window.scrollBy({{ left: {delta_x}, top: {delta_y}, behavior: 'instant' }})"),
+            false,
+        )?;
+        Ok(self)
+    }
+
+    /// Scrolls the page so that the document coordinate (`x`, `y`) is at its top left corner.
+    pub fn scroll_to(&self, x: f64, y: f64) -> Result<&Self> {
+        check_finite_scroll(x, y)?;
+        self.evaluate(
+            &format!("window.scrollTo({{ left: {x}, top: {y}, behavior: 'instant' }})"),
+            false,
+        )?;
+        Ok(self)
+    }
+
+    /// Moves the mouse to `point` and turns the mouse wheel by `delta_x`/`delta_y` CSS pixels
+    /// (dispatches a mouseWheel event).
+    ///
+    /// Unlike `scroll_by`, this scrolls whatever is under the mouse pointer (which may be an
+    /// inner scroll container) and fires `wheel` events. The page scrolls asynchronously, so
+    /// it may not have moved yet when this returns.
+    pub fn mouse_wheel_at_point(&self, point: Point, delta_x: f64, delta_y: f64) -> Result<&Self> {
+        self.move_mouse_to_point(point)?;
+        let buttons = self.mouse_state.lock().unwrap().buttons;
         self.call_method(Input::DispatchMouseEvent {
-            Type: Input::DispatchMouseEventTypeOption::MouseReleased,
+            Type: Input::DispatchMouseEventTypeOption::MouseWheel,
             x: point.x,
             y: point.y,
-            button: Some(Input::MouseButton::Left),
-            click_count: Some(1),
             modifiers: None,
             timestamp: None,
-            buttons: None,
+            button: None,
+            buttons: Some(buttons),
+            click_count: None,
             force: None,
             tangential_pressure: None,
             tilt_x: None,
             tilt_y: None,
             twist: None,
-            delta_x: None,
-            delta_y: None,
+            delta_x: Some(delta_x),
+            delta_y: Some(delta_y),
             pointer_Type: None,
         })?;
         Ok(self)
     }
 
+    /// Keeps scrolling to the bottom of the page for as long as that makes it load more
+    /// content, e.g. on an infinitely scrolling feed.
+    ///
+    /// After each scroll, the page has `idle_timeout` to grow; once it has, we wait (up to
+    /// `idle_timeout` again) until its height has stayed the same for a tenth of
+    /// `idle_timeout` before scrolling again. Stops when the page doesn't grow any more or
+    /// after `max_scrolls` scrolls, and returns how many scrolls loaded new content.
+    pub fn scroll_until_no_new_content(
+        &self,
+        idle_timeout: Duration,
+        max_scrolls: Option<u32>,
+    ) -> Result<u32> {
+        let mut loads = 0;
+        while max_scrolls.is_none_or(|max| loads < max) {
+            let height = self.get_scroll_height()?;
+            self.evaluate(
+                "window.scrollTo({ top: document.scrollingElement.scrollHeight, behavior: 'instant' })",
+                false,
+            )?;
+
+            let grown = util::Wait::with_timeout(idle_timeout).strict_until(
+                || match self.get_scroll_height()? {
+                    new_height if new_height > height => Ok(new_height),
+                    _ => Err(util::Timeout.into()),
+                },
+                Error::downcast::<util::Timeout>,
+            );
+            let mut previous = match grown {
+                Ok(new_height) => new_height,
+                Err(error) if error.is::<util::Timeout>() => break,
+                Err(error) => return Err(error),
+            };
+
+            // the height has to stay the same for a whole quiet period to count as settled
+            let quiet_period = idle_timeout / 10;
+            util::Wait::new(idle_timeout, Duration::ZERO)
+                .strict_until(
+                    || {
+                        std::thread::sleep(quiet_period);
+                        let current = self.get_scroll_height()?;
+                        if current == previous {
+                            Ok(())
+                        } else {
+                            previous = current;
+                            Err(util::Timeout.into())
+                        }
+                    },
+                    Error::downcast::<util::Timeout>,
+                )
+                .or_else(|error| {
+                    // still growing after `idle_timeout`; scroll on regardless
+                    if error.is::<util::Timeout>() {
+                        Ok(())
+                    } else {
+                        Err(error)
+                    }
+                })?;
+
+            loads += 1;
+        }
+
+        debug!("Infinite scroll stopped after {loads} loads");
+        Ok(loads)
+    }
+
+    fn get_scroll_height(&self) -> Result<u64> {
+        self.evaluate("document.scrollingElement.scrollHeight", false)?
+            .value
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("Couldn't read the page's scroll height"))
+    }
+
     /// Capture a screenshot of the current page.
     ///
     /// If `clip` is given, the screenshot is taken of the specified region only.
@@ -1206,103 +2311,630 @@ impl Tab {
         Ok(self)
     }
 
-    /// Set the default background color of the dom.
+    /// Set the default background color of the dom.
+    ///
+    /// Pass a RGBA to override the background color of the dom.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// use headless_chrome::{protocol::page::ScreenshotFormat, Browser, LaunchOptions};
+    /// let browser = Browser::new(LaunchOptions::default_builder().build().unwrap())?;
+    /// let tab = browser.new_tab()?;
+    /// tab.set_background_color( color: RGBA { r: 255, g: 0, b: 0, a: 1.,})?;
+    ///
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_background_color(&self, color: DOM::RGBA) -> Result<&Self> {
+        self.call_method(Emulation::SetDefaultBackgroundColorOverride { color: Some(color) })?;
+        Ok(self)
+    }
+
+    /// Enables the profiler
+    pub fn enable_profiler(&self) -> Result<&Self> {
+        self.call_method(Profiler::Enable(None))?;
+
+        Ok(self)
+    }
+
+    /// Disables the profiler
+    pub fn disable_profiler(&self) -> Result<&Self> {
+        self.call_method(Profiler::Disable(None))?;
+
+        Ok(self)
+    }
+
+    /// Starts tracking which lines of JS have been executed
+    ///
+    /// Will return error unless `enable_profiler` has been called.
+    ///
+    /// Equivalent to hitting the record button in the "coverage" tab in Chrome DevTools.
+    /// See the file `tests/coverage.rs` for an example.
+    ///
+    /// By default we enable the 'detailed' flag on StartPreciseCoverage, which enables block-level
+    /// granularity, and also enable 'call_count' (which when disabled always sets count to 1 or 0).
+    ///
+    pub fn start_js_coverage(&self) -> Result<&Self> {
+        self.call_method(Profiler::StartPreciseCoverage {
+            call_count: Some(true),
+            detailed: Some(true),
+            allow_triggered_updates: None,
+        })?;
+        Ok(self)
+    }
+
+    /// Stops tracking which lines of JS have been executed
+    /// If you're finished with the profiler, don't forget to call `disable_profiler`.
+    pub fn stop_js_coverage(&self) -> Result<&Self> {
+        self.call_method(Profiler::StopPreciseCoverage(None))?;
+        Ok(self)
+    }
+
+    /// Collect coverage data for the current isolate, and resets execution counters.
+    ///
+    /// Precise code coverage needs to have started (see `start_js_coverage`).
+    ///
+    /// Will only send information about code that's been executed since this method was last
+    /// called, or (if this is the first time) since calling `start_js_coverage`.
+    /// Another way of thinking about it is: every time you call this, the call counts for
+    /// FunctionRanges are reset after returning.
+    ///
+    /// The format of the data is a little unintuitive, see here for details:
+    /// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler#type-ScriptCoverage>
+    pub fn take_precise_js_coverage(&self) -> Result<Vec<Profiler::ScriptCoverage>> {
+        let script_coverages = self
+            .call_method(Profiler::TakePreciseCoverage(None))?
+            .result;
+        Ok(script_coverages)
+    }
+
+    /// Enables fetch domain.
+    ///
+    /// Requests matching `patterns` (or all requests, if it's `None`) are paused and passed to
+    /// the `RequestInterceptor`. Requests the tab's routes are interested in are paused too.
+    pub fn enable_fetch(
+        &self,
+        patterns: Option<&[Fetch::RequestPattern]>,
+        handle_auth_requests: Option<bool>,
+    ) -> Result<&Self> {
+        *self.fetch_config.lock().unwrap() = Some(FetchConfig {
+            patterns: patterns.map(Vec::from),
+            handle_auth_requests,
+        });
+        self.sync_fetch()?;
+        Ok(self)
+    }
+
+    /// Disables fetch domain, unless routes added with `route` still need it.
+    pub fn disable_fetch(&self) -> Result<&Self> {
+        *self.fetch_config.lock().unwrap() = None;
+        self.sync_fetch()?;
+        Ok(self)
+    }
+
+    /// Enables or disables the fetch domain with the patterns needed by `enable_fetch`'s
+    /// caller, the routes, `block_resource_types` and the proxy credentials.
+    fn sync_fetch(&self) -> Result<()> {
+        let config = self.fetch_config.lock().unwrap().clone();
+        let proxy_auth = self.proxy_credentials.lock().unwrap().is_some();
+        let mut extra_patterns: Vec<Fetch::RequestPattern> = vec![];
+        // Chrome only reports the authentication challenges of requests it paused
+        if proxy_auth {
+            extra_patterns.push(Fetch::RequestPattern {
+                url_pattern: Some("*".to_string()),
+                resource_Type: None,
+                request_stage: Some(Fetch::RequestStage::Request),
+            });
+        }
+        let blocked_type_patterns = self
+            .blocked_resource_types
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|resource_type| Fetch::RequestPattern {
+                url_pattern: Some("*".to_string()),
+                resource_Type: Some(resource_type.clone()),
+                request_stage: Some(Fetch::RequestStage::Request),
+            })
+            .collect::<Vec<_>>();
+        for pattern in self
+            .routes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(RegisteredRoute::fetch_pattern)
+            .chain(blocked_type_patterns)
+        {
+            if !extra_patterns.contains(&pattern) {
+                extra_patterns.push(pattern);
+            }
+        }
+
+        let (patterns, handle_auth_requests) = match config {
+            None if extra_patterns.is_empty() => {
+                self.call_method(Fetch::Disable(None))?;
+                return Ok(());
+            }
+            None => (Some(extra_patterns), None),
+            // all requests are paused at the request stage already, but response routes
+            // still need their responses paused
+            Some(FetchConfig {
+                patterns: None,
+                handle_auth_requests,
+            }) => {
+                let mut patterns = vec![Fetch::RequestPattern {
+                    url_pattern: Some("*".to_string()),
+                    resource_Type: None,
+                    request_stage: Some(Fetch::RequestStage::Request),
+                }];
+                patterns.extend(extra_patterns);
+                (Some(patterns), handle_auth_requests)
+            }
+            Some(FetchConfig {
+                patterns: Some(mut patterns),
+                handle_auth_requests,
+            }) => {
+                patterns.extend(extra_patterns);
+                (Some(patterns), handle_auth_requests)
+            }
+        };
+        self.call_method(Fetch::Enable {
+            patterns,
+            handle_auth_requests: if proxy_auth {
+                Some(true)
+            } else {
+                handle_auth_requests
+            },
+        })?;
+        Ok(())
+    }
+
+    /// Answers the proxy's authentication challenges with `credentials` (a username and
+    /// password), whatever `enable_fetch` and `authenticate` were called with. `None` stops.
+    pub(crate) fn set_proxy_credentials(
+        &self,
+        credentials: Option<(String, String)>,
+    ) -> Result<&Self> {
+        *self.proxy_credentials.lock().unwrap() =
+            credentials.map(|(username, password)| AuthChallengeResponse {
+                response: Fetch::AuthChallengeResponseResponse::ProvideCredentials,
+                username: Some(username),
+                password: Some(password),
+            });
+        self.sync_fetch()?;
+        Ok(self)
+    }
+
+    /// Routes requests whose URL matches `matcher` to `handler`, which can answer them itself,
+    /// change them, fail them or leave them alone; see `Route`.
+    ///
+    /// Any number of routes can be added. When several match a request, the most recently added
+    /// one gets it first, and each `Route::fallback` hands it to the next one. Requests no route
+    /// handles go to the `RequestInterceptor` set with `enable_request_interception`, which
+    /// continues them unless one was set.
+    ///
+    /// Only requests matching some route's glob are paused. Handlers are run on the tab's event
+    /// handling thread, so they must not wait for events of this tab (e.g. `wait_for_element`).
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::protocol::cdp::Network::ErrorReason;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.route("**/api/user", |route| {
+    ///     route.fulfill(200, &[("Content-Type", "application/json")], r#"{"name":"Ada"}"#)
+    /// })?;
+    /// tab.route("**/*.png", |route| route.abort(ErrorReason::BlockedByClient))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn route<M, H>(&self, matcher: M, handler: H) -> Result<RouteId>
+    where
+        M: Into<UrlMatcher>,
+        H: Fn(&Route) -> Result<()> + Send + Sync + 'static,
+    {
+        self.add_route(matcher.into(), Handler::Request(Arc::new(handler)))
+    }
+
+    /// Routes responses to requests whose URL matches `matcher` to `handler` before the page
+    /// gets them, so that it can inspect and replace them; see `ResponseRoute`.
+    ///
+    /// Response routes work like request routes (see `route`), and are removed with `unroute`
+    /// as well. A request answered by a request route never reaches the response stage.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.route_response("https://example.com/", |response| {
+    ///     let html = response.text()?;
+    ///     response.fulfill_body(html.replace("<head>", "<head><script>window.testing = true</script>"))
+    /// })?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn route_response<M, H>(&self, matcher: M, handler: H) -> Result<RouteId>
+    where
+        M: Into<UrlMatcher>,
+        H: Fn(&ResponseRoute) -> Result<()> + Send + Sync + 'static,
+    {
+        self.add_route(matcher.into(), Handler::Response(Arc::new(handler)))
+    }
+
+    fn add_route(&self, matcher: UrlMatcher, handler: Handler) -> Result<RouteId> {
+        debug!("Adding route for {matcher:?}");
+        let id = RouteId::next();
+        self.routes.lock().unwrap().push(RegisteredRoute {
+            id,
+            matcher,
+            handler,
+        });
+        self.sync_fetch()?;
+        Ok(id)
+    }
+
+    /// Removes a route added with `route`, `route_response` or `route_websocket`. Returns
+    /// whether it was still there.
+    pub fn unroute(&self, id: RouteId) -> Result<bool> {
+        let removed = {
+            let mut routes = self.routes.lock().unwrap();
+            let count = routes.len();
+            routes.retain(|route| route.id != id);
+            routes.len() != count
+        };
+        if !removed {
+            let mocks = self.websocket_mocks.lock().unwrap().clone();
+            return Ok(mocks.is_some_and(|mocks| mocks.remove_route(id)));
+        }
+        self.sync_fetch()?;
+        let update = self.har_updates.lock().unwrap().remove(&id);
+        if let Some(update) = update {
+            self.finish_har_update(update)?;
+        }
+        Ok(true)
+    }
+
+    /// Removes all routes added with `route`, `route_response` and `route_websocket`.
+    pub fn unroute_all(&self) -> Result<&Self> {
+        self.routes.lock().unwrap().clear();
+        if let Some(mocks) = self.websocket_mocks.lock().unwrap().as_ref() {
+            mocks.clear_routes();
+        }
+        self.sync_fetch()?;
+        let updates: Vec<HarUpdate> = self
+            .har_updates
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_id, update)| update)
+            .collect();
+        for update in updates {
+            self.finish_har_update(update)?;
+        }
+        Ok(self)
+    }
+
+    /// Calls `handler` with every WebSocket the page opens from now on, which can then be
+    /// inspected as its handshake completes and frames are sent and received. Pass the
+    /// returned listener to `remove_event_listener` to stop.
+    ///
+    /// Sockets mocked with `route_websocket` never reach the network, so they aren't seen here.
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.on_websocket(|socket| {
+    ///     println!("opened {}", socket.url());
+    ///     socket.on_frame(|frame| println!("{:?}: {}", frame.direction, frame.payload));
+    /// })?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_websocket<F>(&self, handler: F) -> Result<Weak<SyncSendEvent>>
+    where
+        F: Fn(&WebSocket) + Send + Sync + 'static,
+    {
+        self.enable_network()?;
+        let tracker = WebSocketTracker::new(Box::new(handler));
+        self.add_event_listener(Arc::new(move |event: &Event| tracker.on_event(event)))
+    }
+
+    /// Mocks WebSockets the page opens to URLs matching `matcher`, with `handler` playing the
+    /// part of the server; see `WebSocketRoute`. The most recently added matching route mocks
+    /// a socket, and sockets no route matches connect as usual. Remove the route with
+    /// `unroute`.
+    ///
+    /// This works by replacing `window.WebSocket` in the page (and in the documents it loads
+    /// later), so the page sees the socket open as soon as `handler` is called. Only text
+    /// messages are supported, and sockets opened by workers or iframes connect as usual,
+    /// without being mocked. Navigating away closes the mocked sockets of the previous page.
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.route_websocket("wss://example.com/chat", |socket| {
+    ///     let server = socket.clone();
+    ///     socket.on_message(move |message| {
+    ///         server.send(&format!("echo: {message}")).ok();
+    ///     });
+    ///     socket.send("welcome")
+    /// })?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn route_websocket<M, H>(&self, matcher: M, handler: H) -> Result<RouteId>
+    where
+        M: Into<UrlMatcher>,
+        H: Fn(&WebSocketRoute) -> Result<()> + Send + Sync + 'static,
+    {
+        let matcher = matcher.into();
+        debug!("Adding WebSocket route for {matcher:?}");
+        let id = RouteId::next();
+        self.install_websocket_mocks()?
+            .add_route(id, matcher, Arc::new(handler));
+        Ok(id)
+    }
+
+    fn install_websocket_mocks(&self) -> Result<Arc<WebSocketMocks>> {
+        if let Some(mocks) = self.websocket_mocks.lock().unwrap().as_ref() {
+            return Ok(Arc::clone(mocks));
+        }
+
+        let mocks = Arc::new(WebSocketMocks::new(
+            Arc::clone(&self.transport),
+            self.session_id.clone(),
+        ));
+        let binding_mocks = Arc::clone(&mocks);
+        self.page_bindings.lock().unwrap().insert(
+            websocket::BINDING_NAME.to_string(),
+            Arc::new(move |payload: Json| binding_mocks.on_binding_called(&payload)),
+        );
+        self.call_method(AddBinding {
+            name: websocket::BINDING_NAME.to_string(),
+            execution_context_id: None,
+            execution_context_name: None,
+        })?;
+        self.call_method(AddScriptToEvaluateOnNewDocument {
+            source: websocket::MOCK_SCRIPT.to_string(),
+            world_name: None,
+            include_command_line_api: None,
+            run_immediately: None,
+        })?;
+        self.evaluate(websocket::MOCK_SCRIPT, false)?;
+
+        let navigation_mocks = Arc::clone(&mocks);
+        self.add_event_listener(Arc::new(move |event: &Event| {
+            if let Event::PageFrameNavigated(ev) = event {
+                if ev.params.frame.parent_id.is_none() {
+                    navigation_mocks.on_navigated();
+                }
+            }
+        }))?;
+
+        *self.websocket_mocks.lock().unwrap() = Some(Arc::clone(&mocks));
+        Ok(mocks)
+    }
+
+    /// Blocks requests to URLs matching any of `patterns`, in which `*` matches any sequence of
+    /// characters, e.g. `*.doubleclick.net/*`. Replaces the patterns of previous calls, so an
+    /// empty slice unblocks everything again.
+    ///
+    /// Unlike a route, this doesn't pause any requests.
+    pub fn block_urls(&self, patterns: &[&str]) -> Result<&Self> {
+        debug!("Blocking URLs: {patterns:?}");
+        self.enable_network()?;
+        self.call_method(Network::SetBlockedURLs {
+            urls: patterns.iter().map(ToString::to_string).collect(),
+        })?;
+        Ok(self)
+    }
+
+    /// Fails all requests for resources of the given types, e.g. `ResourceType::Image` and
+    /// `ResourceType::Font`, before any route sees them. Replaces the types of previous calls,
+    /// so an empty slice unblocks everything again.
+    ///
+    /// Only requests of these types are paused to do this.
+    pub fn block_resource_types(&self, resource_types: &[Network::ResourceType]) -> Result<&Self> {
+        debug!("Blocking resource types: {resource_types:?}");
+        *self.blocked_resource_types.lock().unwrap() = resource_types.to_vec();
+        self.sync_fetch()?;
+        Ok(self)
+    }
+
+    /// How many requests `block_urls` and `block_resource_types` have blocked so far.
+    pub fn blocked_request_counts(&self) -> BlockedRequestCounts {
+        *self.blocked_request_counts.lock().unwrap()
+    }
+
+    /// Makes the tab ignore the HTTP cache for every request while `disabled` is true, e.g. for
+    /// cold-cache performance runs.
+    pub fn set_cache_disabled(&self, disabled: bool) -> Result<&Self> {
+        self.enable_network()?;
+        self.call_method(Network::SetCacheDisabled {
+            cache_disabled: disabled,
+        })?;
+        Ok(self)
+    }
+
+    /// Empties the HTTP cache. The cache is shared by the tabs of a browser context, so this
+    /// affects them all.
+    pub fn clear_browser_cache(&self) -> Result<&Self> {
+        self.call_method(Network::ClearBrowserCache(None))?;
+        Ok(self)
+    }
+
+    /// Makes the tab's requests skip service workers, and go to the network (or the HTTP
+    /// cache) instead, while `bypass` is true.
+    pub fn set_bypass_service_worker(&self, bypass: bool) -> Result<&Self> {
+        self.enable_network()?;
+        self.call_method(Network::SetBypassServiceWorker { bypass })?;
+        Ok(self)
+    }
+
+    /// Clears the given kinds of data stored for `origin` (e.g. `https://example.com`), such
+    /// as its local storage or service workers. Pass `StorageType::All` to clear everything.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::protocol::cdp::Storage::StorageType;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.clear_data_for_origin(
+    ///     "https://example.com",
+    ///     &[StorageType::LocalStorage, StorageType::CacheStorage],
+    /// )?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn clear_data_for_origin(
+        &self,
+        origin: &str,
+        storage_types: &[Storage::StorageType],
+    ) -> Result<&Self> {
+        if storage_types.is_empty() {
+            return Ok(self);
+        }
+        let storage_types = storage_types
+            .iter()
+            .map(|storage_type| match serde_json::to_value(storage_type)? {
+                Json::String(name) => Ok(name),
+                other => Err(anyhow::anyhow!("unexpected storage type {other}")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.call_method(Storage::ClearDataForOrigin {
+            origin: origin.to_string(),
+            storage_Types: storage_types.join(","),
+        })?;
+        Ok(self)
+    }
+
+    /// Answers requests with the responses recorded in the HAR file at `path`, e.g. one saved
+    /// from `stop_har_recording`, so that pages can be loaded without a network.
     ///
-    /// Pass a RGBA to override the background color of the dom.
+    /// This adds a route (see `route`) for the requests matching `options.url`, which can be
+    /// removed with `unroute`. Recorded redirects are replayed, and recorded failures fail
+    /// again.
     ///
     /// ```rust,no_run
     /// # use anyhow::Result;
     /// # fn main() -> Result<()> {
     /// #
-    /// use headless_chrome::{protocol::page::ScreenshotFormat, Browser, LaunchOptions};
-    /// let browser = Browser::new(LaunchOptions::default_builder().build().unwrap())?;
-    /// let tab = browser.new_tab()?;
-    /// tab.set_background_color( color: RGBA { r: 255, g: 0, b: 0, a: 1.,})?;
-    ///
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::browser::tab::har::HarReplayOptions;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.route_from_har("tests/example.har", HarReplayOptions::default())?;
+    /// tab.navigate_to("https://example.com")?.wait_until_navigated()?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_background_color(&self, color: DOM::RGBA) -> Result<&Self> {
-        self.call_method(Emulation::SetDefaultBackgroundColorOverride { color: Some(color) })?;
-        Ok(self)
-    }
-
-    /// Enables the profiler
-    pub fn enable_profiler(&self) -> Result<&Self> {
-        self.call_method(Profiler::Enable(None))?;
-
-        Ok(self)
-    }
+    pub fn route_from_har<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: HarReplayOptions,
+    ) -> Result<RouteId> {
+        let path = path.as_ref();
+        let archive = if options.update && !path.exists() {
+            Har::default()
+        } else {
+            Har::from_file(path)?
+        };
+        let archive = Arc::new(archive);
 
-    /// Disables the profiler
-    pub fn disable_profiler(&self) -> Result<&Self> {
-        self.call_method(Profiler::Disable(None))?;
+        let recording = if options.update {
+            Some(self.record_har(HarRecordingOptions {
+                capture_content: true,
+            })?)
+        } else {
+            None
+        };
 
-        Ok(self)
-    }
+        let handler_archive = Arc::clone(&archive);
+        let handler_options = options.clone();
+        let matcher = options
+            .url
+            .clone()
+            .unwrap_or_else(|| UrlMatcher::glob("**"));
+        let routed = self.route(matcher, move |route| {
+            match handler_options.find_entry(&handler_archive, route.request()) {
+                Some(entry) => har::fulfill_from_entry(route, entry),
+                None if handler_options.update => route.fallback(),
+                None => match handler_options.not_found {
+                    HarNotFound::Abort => route.abort(Network::ErrorReason::Failed),
+                    HarNotFound::Fallback => route.fallback(),
+                },
+            }
+        });
+        let id = match routed {
+            Ok(id) => id,
+            Err(error) => {
+                if let Some(recording) = recording {
+                    self.remove_event_listener(&recording.listener)?;
+                }
+                return Err(error);
+            }
+        };
 
-    /// Starts tracking which lines of JS have been executed
-    ///
-    /// Will return error unless `enable_profiler` has been called.
-    ///
-    /// Equivalent to hitting the record button in the "coverage" tab in Chrome DevTools.
-    /// See the file `tests/coverage.rs` for an example.
-    ///
-    /// By default we enable the 'detailed' flag on StartPreciseCoverage, which enables block-level
-    /// granularity, and also enable 'call_count' (which when disabled always sets count to 1 or 0).
-    ///
-    pub fn start_js_coverage(&self) -> Result<&Self> {
-        self.call_method(Profiler::StartPreciseCoverage {
-            call_count: Some(true),
-            detailed: Some(true),
-            allow_triggered_updates: None,
-        })?;
-        Ok(self)
+        if let Some(recording) = recording {
+            self.har_updates.lock().unwrap().insert(
+                id,
+                HarUpdate {
+                    path: path.to_path_buf(),
+                    archive,
+                    options,
+                    recording,
+                },
+            );
+        }
+        Ok(id)
     }
 
-    /// Stops tracking which lines of JS have been executed
-    /// If you're finished with the profiler, don't forget to call `disable_profiler`.
-    pub fn stop_js_coverage(&self) -> Result<&Self> {
-        self.call_method(Profiler::StopPreciseCoverage(None))?;
+    /// Writes the requests recorded so far by the `route_from_har` routes in update mode to
+    /// their archive files, without removing the routes. Removing the routes saves them too,
+    /// but nothing is saved when the tab is dropped.
+    pub fn save_har_updates(&self) -> Result<&Self> {
+        for update in self.har_updates.lock().unwrap().values() {
+            let recorded = update.recording.recorder.lock().unwrap().clone().finish();
+            Self::save_har_update(update, recorded)?;
+        }
         Ok(self)
     }
 
-    /// Collect coverage data for the current isolate, and resets execution counters.
-    ///
-    /// Precise code coverage needs to have started (see `start_js_coverage`).
-    ///
-    /// Will only send information about code that's been executed since this method was last
-    /// called, or (if this is the first time) since calling `start_js_coverage`.
-    /// Another way of thinking about it is: every time you call this, the call counts for
-    /// FunctionRanges are reset after returning.
-    ///
-    /// The format of the data is a little unintuitive, see here for details:
-    /// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler#type-ScriptCoverage>
-    pub fn take_precise_js_coverage(&self) -> Result<Vec<Profiler::ScriptCoverage>> {
-        let script_coverages = self
-            .call_method(Profiler::TakePreciseCoverage(None))?
-            .result;
-        Ok(script_coverages)
-    }
-
-    /// Enables fetch domain.
-    pub fn enable_fetch(
-        &self,
-        patterns: Option<&[Fetch::RequestPattern]>,
-        handle_auth_requests: Option<bool>,
-    ) -> Result<&Self> {
-        self.call_method(Fetch::Enable {
-            patterns: patterns.map(Vec::from),
-            handle_auth_requests,
-        })?;
-        Ok(self)
+    fn finish_har_update(&self, update: HarUpdate) -> Result<()> {
+        let recorded = self.finish_har_recording(&update.recording)?;
+        Self::save_har_update(&update, recorded)
     }
 
-    /// Disables fetch domain
-    pub fn disable_fetch(&self) -> Result<&Self> {
-        self.call_method(Fetch::Disable(None))?;
-        Ok(self)
+    fn save_har_update(update: &HarUpdate, recorded: Har) -> Result<()> {
+        let mut archive = (*update.archive).clone();
+        update.options.add_new_entries(&mut archive, recorded);
+        archive.save(&update.path)
     }
 
     /// Allows you to inspect outgoing network requests from the tab, and optionally return
@@ -1349,13 +2981,7 @@ impl Tab {
         handler_name: S,
         handler: ResponseHandler,
     ) -> Result<Option<ResponseHandler>> {
-        self.call_method(Network::Enable {
-            max_total_buffer_size: None,
-            max_resource_buffer_size: None,
-            max_post_data_size: None,
-            report_direct_socket_traffic: None,
-            enable_durable_messages: None,
-        })?;
+        self.enable_network()?;
         Ok(self
             .response_handler
             .lock()
@@ -1368,13 +2994,7 @@ impl Tab {
         handler_name: S,
         handler: LoadingFailedHandler,
     ) -> Result<Option<LoadingFailedHandler>> {
-        self.call_method(Network::Enable {
-            max_total_buffer_size: None,
-            max_resource_buffer_size: None,
-            max_post_data_size: None,
-            report_direct_socket_traffic: None,
-            enable_durable_messages: None,
-        })?;
+        self.enable_network()?;
         Ok(self
             .loading_failed_handler
             .lock()
@@ -1398,6 +3018,263 @@ impl Tab {
         Ok(())
     }
 
+    /// Blocks until a response whose URL matches `matcher` has finished loading, then returns it.
+    ///
+    /// `matcher` can be a glob (`"**/api/save"`), a `regex::Regex`, or a closure wrapped in
+    /// `UrlMatcher::predicate`. The body is only fetched when `NetworkResponse::body` is called.
+    ///
+    /// Only responses which finish loading *after* this method is called are considered.
+    pub fn wait_for_response<M: Into<UrlMatcher>>(&self, matcher: M) -> Result<NetworkResponse> {
+        self.wait_for_response_with_custom_timeout(matcher, *self.default_timeout.read().unwrap())
+    }
+
+    pub fn wait_for_response_with_custom_timeout<M: Into<UrlMatcher>>(
+        &self,
+        matcher: M,
+        timeout: Duration,
+    ) -> Result<NetworkResponse> {
+        let matcher = matcher.into();
+        debug!("Waiting for response matching: {matcher:?}");
+
+        let (tx, rx) = mpsc::channel();
+        let handler_name = format!(
+            "wait_for_response_{}",
+            WAITER_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
+        );
+        self.register_response_handling(
+            &handler_name,
+            Box::new(move |params, _fetch_body| {
+                if matcher.matches(&params.response.url) {
+                    tx.send(params).ok();
+                }
+            }),
+        )?;
+
+        let received = rx.recv_timeout(timeout);
+        self.deregister_response_handling(&handler_name)?;
+
+        let params = received.map_err(|_| util::Timeout)?;
+        Ok(NetworkResponse::new(
+            params,
+            Arc::clone(&self.transport),
+            self.session_id.clone(),
+        ))
+    }
+
+    /// Blocks until the tab sends a request whose URL matches `matcher`, then returns it.
+    ///
+    /// See `wait_for_response` for the accepted matchers. Only requests which are sent *after*
+    /// this method is called are considered.
+    pub fn wait_for_request<M: Into<UrlMatcher>>(
+        &self,
+        matcher: M,
+    ) -> Result<RequestWillBeSentEventParams> {
+        self.wait_for_request_with_custom_timeout(matcher, *self.default_timeout.read().unwrap())
+    }
+
+    pub fn wait_for_request_with_custom_timeout<M: Into<UrlMatcher>>(
+        &self,
+        matcher: M,
+        timeout: Duration,
+    ) -> Result<RequestWillBeSentEventParams> {
+        let matcher = matcher.into();
+        debug!("Waiting for request matching: {matcher:?}");
+
+        self.enable_network()?;
+
+        let (tx, rx) = mpsc::channel();
+        let listener = self.add_event_listener(Arc::new(move |event: &Event| {
+            if let Event::NetworkRequestWillBeSent(ev) = event {
+                if matcher.matches(&ev.params.request.url) {
+                    tx.send(ev.params.clone()).ok();
+                }
+            }
+        }))?;
+
+        let received = rx.recv_timeout(timeout);
+        self.remove_event_listener(&listener)?;
+
+        Ok(received.map_err(|_| util::Timeout)?)
+    }
+
+    /// Starts recording the tab's network traffic into a HAR, without response bodies; see
+    /// `start_har_recording_with_options`.
+    pub fn start_har_recording(&self) -> Result<&Self> {
+        self.start_har_recording_with_options(HarRecordingOptions::default())
+    }
+
+    /// Starts recording the tab's network traffic into a HAR, which `stop_har_recording`
+    /// returns. Starting again discards the current recording.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::browser::tab::har::HarRecordingOptions;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.start_har_recording_with_options(HarRecordingOptions { capture_content: true })?;
+    /// tab.navigate_to("https://example.com")?.wait_until_navigated()?;
+    /// let har = tab.stop_har_recording()?;
+    /// har.save(std::env::temp_dir().join("example.har"))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_har_recording_with_options(&self, options: HarRecordingOptions) -> Result<&Self> {
+        if let Some(previous) = self.har_recording.lock().unwrap().take() {
+            self.remove_event_listener(&previous.listener)?;
+        }
+        let recording = self.record_har(options)?;
+        *self.har_recording.lock().unwrap() = Some(recording);
+        Ok(self)
+    }
+
+    /// Stops the recording started with `start_har_recording` and returns it. Requests which
+    /// haven't received a response yet are left out.
+    pub fn stop_har_recording(&self) -> Result<Har> {
+        let recording = self
+            .har_recording
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("HAR recording wasn't started"))?;
+        self.finish_har_recording(&recording)
+    }
+
+    /// Starts logging the tab's requests, replacing the log of any previous call.
+    ///
+    /// The returned `NetworkLog` keeps a `RequestRecord` per request, with its redirects,
+    /// response, timing, size and failure, and can be queried and cleared while the tab keeps
+    /// adding to it.
+    ///
+    /// ```rust,no_run
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::browser::tab::network_log::{NetworkLogOptions, RequestFilter};
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// let log = tab.start_network_log(NetworkLogOptions::default())?;
+    /// tab.navigate_to("https://example.com")?.wait_until_navigated()?;
+    /// let failed = log.filter(&RequestFilter {
+    ///     failed: Some(true),
+    ///     ..Default::default()
+    /// });
+    /// assert!(failed.is_empty());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_network_log(&self, options: NetworkLogOptions) -> Result<NetworkLog> {
+        self.stop_network_log()?;
+        self.enable_network()?;
+
+        let log = NetworkLog::new(options);
+        let listener_log = log.clone();
+        let listener = self.add_event_listener(Arc::new(move |event: &Event| {
+            listener_log.on_event(event);
+        }))?;
+        *self.network_log.lock().unwrap() = Some(NetworkLogging {
+            log: log.clone(),
+            listener,
+        });
+        Ok(log)
+    }
+
+    /// Stops adding to the log started with `start_network_log`, if any. The records already
+    /// in it are kept.
+    pub fn stop_network_log(&self) -> Result<()> {
+        let logging = self.network_log.lock().unwrap().take();
+        if let Some(logging) = logging {
+            self.remove_event_listener(&logging.listener)?;
+        }
+        Ok(())
+    }
+
+    /// The log started with `start_network_log`, if it's still running.
+    pub fn network_log(&self) -> Option<NetworkLog> {
+        self.network_log
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|logging| logging.log.clone())
+    }
+
+    fn record_har(&self, options: HarRecordingOptions) -> Result<HarRecording> {
+        self.enable_network()?;
+
+        let recorder = Arc::new(Mutex::new(HarRecorder::new(options)));
+        let listener_recorder = Arc::clone(&recorder);
+        let transport = Arc::clone(&self.transport);
+        let session_id = self.session_id.clone();
+        let listener = self.add_event_listener(Arc::new(move |event: &Event| {
+            listener_recorder
+                .lock()
+                .unwrap()
+                .on_event(event, |request_id| {
+                    network::get_response_body(&transport, &session_id, request_id)
+                });
+        }))?;
+        Ok(HarRecording { recorder, listener })
+    }
+
+    fn finish_har_recording(&self, recording: &HarRecording) -> Result<Har> {
+        self.remove_event_listener(&recording.listener)?;
+        let recorder = std::mem::replace(
+            &mut *recording.recorder.lock().unwrap(),
+            HarRecorder::new(HarRecordingOptions::default()),
+        );
+        Ok(recorder.finish())
+    }
+
+    /// Emulates slow or unavailable network connections, e.g. with a preset like
+    /// `NetworkConditions::SLOW_3G`. Pass `NetworkConditions::default()` to stop emulating.
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # use headless_chrome::browser::tab::network::NetworkConditions;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// tab.emulate_network(NetworkConditions::OFFLINE)?;
+    /// let online: bool = tab.evaluate_typed("navigator.onLine")?;
+    /// assert!(!online);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emulate_network(&self, conditions: NetworkConditions) -> Result<&Self> {
+        debug!("Emulating network conditions: {conditions:?}");
+        self.enable_network()?;
+        self.call_method(Network::EmulateNetworkConditions {
+            offline: conditions.offline,
+            latency: conditions.latency,
+            download_throughput: conditions.download_throughput,
+            upload_throughput: conditions.upload_throughput,
+            connection_Type: conditions.connection_type,
+            packet_loss: None,
+            packet_queue_length: None,
+            packet_reordering: None,
+        })?;
+        Ok(self)
+    }
+
+    fn enable_network(&self) -> Result<()> {
+        self.call_method(Network::Enable {
+            max_total_buffer_size: None,
+            max_resource_buffer_size: None,
+            max_post_data_size: None,
+            report_direct_socket_traffic: None,
+            enable_durable_messages: None,
+        })?;
+        Ok(())
+    }
+
     /// Enables runtime domain.
     pub fn enable_runtime(&self) -> Result<&Self> {
         self.call_method(Runtime::Enable(None))?;
@@ -1498,6 +3375,94 @@ impl Tab {
         Ok(result)
     }
 
+    /// Evaluates `expression` on the global object, awaiting the result if it's a promise, and
+    /// deserializes it into `T`. Exceptions thrown by the expression become a `JsException`.
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// let sum: u32 = tab.evaluate_typed("Promise.resolve(1 + 2)")?;
+    /// assert_eq!(sum, 3);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn evaluate_typed<T: DeserializeOwned>(&self, expression: &str) -> Result<T> {
+        let result = self.evaluate_returning(expression, true)?;
+        Ok(serde_json::from_value(result.value.unwrap_or_default())?)
+    }
+
+    /// Like `evaluate_typed`, but leaves the result in the page and returns a handle to it,
+    /// e.g. to pass it to `call_function` later.
+    pub fn evaluate_handle(&self, expression: &str) -> Result<JsHandle<'_>> {
+        let result = self.evaluate_returning(expression, false)?;
+        Ok(JsHandle::new(self, result))
+    }
+
+    /// Calls `function_declaration` with `args`, awaiting any promise it returns, and
+    /// deserializes the result into `R`.
+    ///
+    /// `args` is a tuple of positional arguments (see `CallArgs`), e.g. `()` for none or
+    /// `(value,)` for one, each serialized to JSON. `Element`s and `JsHandle`s in the tuple are
+    /// passed by reference rather than by value. Exceptions thrown by the function become a
+    /// `JsException`.
+    ///
+    /// ```rust
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// #
+    /// # use headless_chrome::Browser;
+    /// # let browser = Browser::default()?;
+    /// # let tab = browser.new_tab()?;
+    /// let joined: String = tab.call_function("(a, b) => a + b", ("foo", "bar"))?;
+    /// assert_eq!(joined, "foobar");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_function<A: CallArgs, R: DeserializeOwned>(
+        &self,
+        function_declaration: &str,
+        args: A,
+    ) -> Result<R> {
+        self.evaluate_handle("globalThis")?
+            .call_function(function_declaration, args)
+    }
+
+    fn evaluate_returning(
+        &self,
+        expression: &str,
+        return_by_value: bool,
+    ) -> Result<Runtime::RemoteObject> {
+        let result = self.call_method(Runtime::Evaluate {
+            expression: expression.to_string(),
+            return_by_value: Some(return_by_value),
+            generate_preview: None,
+            silent: Some(true),
+            await_promise: Some(true),
+            include_command_line_api: Some(false),
+            user_gesture: Some(false),
+            object_group: None,
+            context_id: None,
+            throw_on_side_effect: None,
+            timeout: None,
+            disable_breaks: None,
+            repl_mode: None,
+            allow_unsafe_eval_blocked_by_csp: None,
+            unique_context_id: None,
+            serialization_options: None,
+        })?;
+
+        if let Some(details) = result.exception_details {
+            return Err(JsException::from(details).into());
+        }
+        Ok(result.result)
+    }
+
     /// Adds event listener to Event
     ///
     /// Make sure you are enabled domain you are listening events to.
@@ -1739,13 +3704,7 @@ impl Tab {
     }
 
     pub fn set_extra_http_headers(&self, headers: HashMap<&str, &str>) -> Result<()> {
-        self.call_method(Network::Enable {
-            max_total_buffer_size: None,
-            max_resource_buffer_size: None,
-            max_post_data_size: None,
-            report_direct_socket_traffic: None,
-            enable_durable_messages: None,
-        })?;
+        self.enable_network()?;
         self.call_method(SetExtraHTTPHeaders {
             headers: Network::Headers(Some(json!(headers))),
         })?;
@@ -0,0 +1,286 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::browser::transport::{SessionId, Transport};
+use crate::protocol::cdp::Network::{
+    ConnectionType, GetResponseBody, GetResponseBodyReturnObject, RequestId,
+    events::ResponseReceivedEventParams,
+};
+
+type UrlPredicate = dyn Fn(&str) -> bool + Send + Sync;
+
+/// Decides which URLs a network helper (e.g. `Tab::wait_for_response`) is interested in.
+///
+/// A matcher can be built from a glob (`&str` / `String`), a [`Regex`], or an arbitrary closure
+/// via [`UrlMatcher::predicate`].
+///
+/// In globs, `**` matches any sequence of characters, `*` matches any sequence of characters
+/// except `/`, and every other character (including `?`) is matched literally.
+#[derive(Clone)]
+pub struct UrlMatcher(Matcher);
+
+#[derive(Clone)]
+enum Matcher {
+    Glob { pattern: String, regex: Regex },
+    Regex(Regex),
+    Predicate(Arc<UrlPredicate>),
+}
+
+impl UrlMatcher {
+    pub fn glob(pattern: &str) -> Self {
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '*' {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    regex.push_str(".*");
+                } else {
+                    regex.push_str("[^/]*");
+                }
+            } else {
+                regex.push_str(&regex::escape(&c.to_string()));
+            }
+        }
+        regex.push('$');
+
+        Self(Matcher::Glob {
+            pattern: pattern.to_string(),
+            // every non-wildcard character is escaped, so this always compiles
+            regex: Regex::new(&regex).expect("glob produced an invalid regex"),
+        })
+    }
+
+    pub fn regex(regex: Regex) -> Self {
+        Self(Matcher::Regex(regex))
+    }
+
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self(Matcher::Predicate(Arc::new(predicate)))
+    }
+
+    pub fn matches(&self, url: &str) -> bool {
+        match &self.0 {
+            Matcher::Glob { regex, .. } | Matcher::Regex(regex) => regex.is_match(url),
+            Matcher::Predicate(predicate) => predicate(url),
+        }
+    }
+
+    /// A `Fetch.enable` URL pattern matching at least the URLs this matcher does. Only globs
+    /// can be narrowed down; regexes and predicates pause every request.
+    pub(crate) fn fetch_url_pattern(&self) -> String {
+        match &self.0 {
+            Matcher::Glob { pattern, .. } => {
+                let mut fetch_pattern = String::new();
+                for c in pattern.chars() {
+                    match c {
+                        '*' if fetch_pattern.ends_with('*') => {}
+                        '?' | '\\' => {
+                            fetch_pattern.push('\\');
+                            fetch_pattern.push(c);
+                        }
+                        _ => fetch_pattern.push(c),
+                    }
+                }
+                fetch_pattern
+            }
+            Matcher::Regex(_) | Matcher::Predicate(_) => "*".to_string(),
+        }
+    }
+}
+
+impl Debug for UrlMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match &self.0 {
+            Matcher::Glob { pattern, .. } => write!(f, "UrlMatcher::Glob({pattern:?})"),
+            Matcher::Regex(regex) => write!(f, "UrlMatcher::Regex({:?})", regex.as_str()),
+            Matcher::Predicate(_) => write!(f, "UrlMatcher::Predicate"),
+        }
+    }
+}
+
+impl From<&str> for UrlMatcher {
+    fn from(pattern: &str) -> Self {
+        Self::glob(pattern)
+    }
+}
+
+impl From<String> for UrlMatcher {
+    fn from(pattern: String) -> Self {
+        Self::glob(&pattern)
+    }
+}
+
+impl From<Regex> for UrlMatcher {
+    fn from(regex: Regex) -> Self {
+        Self::regex(regex)
+    }
+}
+
+/// Network conditions for `Tab::emulate_network` and `Context::emulate_network`.
+///
+/// The presets match those of Chrome's DevTools, and `default()` turns emulation off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConditions {
+    /// Whether the network seems to be unavailable; this also sets `navigator.onLine`
+    pub offline: bool,
+    /// Minimum latency of requests in milliseconds
+    pub latency: f64,
+    /// In bytes per second, -1 for no limit
+    pub download_throughput: f64,
+    /// In bytes per second, -1 for no limit
+    pub upload_throughput: f64,
+    /// What `navigator.connection` reports
+    pub connection_type: Option<ConnectionType>,
+}
+
+impl NetworkConditions {
+    pub const SLOW_3G: Self = Self {
+        offline: false,
+        latency: 2000.0,
+        download_throughput: 50_000.0,
+        upload_throughput: 50_000.0,
+        connection_type: Some(ConnectionType::Cellular3G),
+    };
+    pub const FAST_3G: Self = Self {
+        offline: false,
+        latency: 562.5,
+        download_throughput: 180_000.0,
+        upload_throughput: 84_375.0,
+        connection_type: Some(ConnectionType::Cellular3G),
+    };
+    pub const FAST_4G: Self = Self {
+        offline: false,
+        latency: 165.0,
+        download_throughput: 1_012_500.0,
+        upload_throughput: 168_750.0,
+        connection_type: Some(ConnectionType::Cellular4G),
+    };
+    pub const OFFLINE: Self = Self {
+        offline: true,
+        latency: 0.0,
+        download_throughput: -1.0,
+        upload_throughput: -1.0,
+        connection_type: Some(ConnectionType::None),
+    };
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            latency: 0.0,
+            download_throughput: -1.0,
+            upload_throughput: -1.0,
+            connection_type: None,
+        }
+    }
+}
+
+/// How many requests a tab has blocked with `Tab::block_urls` and
+/// `Tab::block_resource_types`, see `Tab::blocked_request_counts`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockedRequestCounts {
+    pub by_url: usize,
+    pub by_resource_type: usize,
+}
+
+impl BlockedRequestCounts {
+    pub fn total(&self) -> usize {
+        self.by_url + self.by_resource_type
+    }
+}
+
+/// A response seen by a tab, as returned by `Tab::wait_for_response`.
+///
+/// The body isn't fetched until [`NetworkResponse::body`] is called.
+pub struct NetworkResponse {
+    pub params: ResponseReceivedEventParams,
+    transport: Arc<Transport>,
+    session_id: SessionId,
+}
+
+impl Debug for NetworkResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "NetworkResponse {}", self.params.response.url)
+    }
+}
+
+impl NetworkResponse {
+    pub(crate) fn new(
+        params: ResponseReceivedEventParams,
+        transport: Arc<Transport>,
+        session_id: SessionId,
+    ) -> Self {
+        Self {
+            params,
+            transport,
+            session_id,
+        }
+    }
+
+    /// Fetches the body of this response from the browser.
+    pub fn body(&self) -> Result<GetResponseBodyReturnObject> {
+        get_response_body(
+            &self.transport,
+            &self.session_id,
+            self.params.request_id.clone(),
+        )
+    }
+}
+
+pub(crate) fn get_response_body(
+    transport: &Transport,
+    session_id: &SessionId,
+    request_id: RequestId,
+) -> Result<GetResponseBodyReturnObject> {
+    transport.call_method_on_target(session_id.clone(), GetResponseBody { request_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UrlMatcher;
+
+    #[test]
+    fn glob_matching() {
+        let matcher = UrlMatcher::from("**/api/*");
+        assert!(matcher.matches("http://127.0.0.1:8000/api/save"));
+        assert!(!matcher.matches("http://127.0.0.1:8000/api/save/draft"));
+
+        let matcher = UrlMatcher::from("http://example.com/search?q=*");
+        assert!(matcher.matches("http://example.com/search?q=rust"));
+        assert!(!matcher.matches("http://example.com/searchXq=rust"));
+
+        let matcher = UrlMatcher::from("**/*.js");
+        assert!(matcher.matches("https://cdn.example.com/lib/app.js"));
+        assert!(!matcher.matches("https://cdn.example.com/lib/app.json"));
+    }
+
+    #[test]
+    fn regex_and_predicate_matching() {
+        let matcher = UrlMatcher::from(regex::Regex::new(r"/api/(save|load)$").unwrap());
+        assert!(matcher.matches("http://localhost/api/load"));
+        assert!(!matcher.matches("http://localhost/api/delete"));
+
+        let matcher = UrlMatcher::predicate(|url| url.starts_with("https://"));
+        assert!(matcher.matches("https://example.com"));
+        assert!(!matcher.matches("http://example.com"));
+    }
+
+    #[test]
+    fn fetch_url_patterns() {
+        assert_eq!(UrlMatcher::from("**/api/*").fetch_url_pattern(), "*/api/*");
+        assert_eq!(
+            UrlMatcher::from("http://example.com/search?q=*").fetch_url_pattern(),
+            "http://example.com/search\\?q=*"
+        );
+        let regex = regex::Regex::new(r"\.png$").unwrap();
+        assert_eq!(UrlMatcher::from(regex).fetch_url_pattern(), "*");
+    }
+}
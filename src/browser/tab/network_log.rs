@@ -0,0 +1,386 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::network::UrlMatcher;
+use crate::protocol::cdp::Network::{
+    self, RequestId,
+    events::{LoadingFailedEventParams, RequestWillBeSentEventParams},
+};
+use crate::protocol::cdp::types::Event;
+
+/// Options for `Tab::start_network_log`.
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkLogOptions {
+    /// How many requests to keep. Once there are more, the oldest ones are evicted.
+    pub max_records: usize,
+}
+
+impl Default for NetworkLogOptions {
+    fn default() -> Self {
+        Self { max_records: 1000 }
+    }
+}
+
+/// A redirect a request went through, i.e. a request and the 3xx response to it.
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    pub request: Network::Request,
+    pub response: Network::Response,
+}
+
+/// Why a request failed, from `Network.loadingFailed`.
+#[derive(Debug, Clone)]
+pub struct RequestFailure {
+    /// E.g. `net::ERR_FAILED`
+    pub error_text: String,
+    pub canceled: bool,
+    pub blocked_reason: Option<Network::BlockedReason>,
+}
+
+/// Everything the log knows about one request, from being sent to finishing or failing.
+#[derive(Debug, Clone)]
+pub struct RequestRecord {
+    pub request_id: RequestId,
+    /// The last request made, i.e. the one after all redirects
+    pub request: Network::Request,
+    /// The redirects which led to `request`, oldest first
+    pub redirects: Vec<Redirect>,
+    pub resource_type: Option<Network::ResourceType>,
+    pub initiator: Network::Initiator,
+    pub response: Option<Network::Response>,
+    /// When the first request was sent, in seconds since the epoch
+    pub wall_time: f64,
+    /// When the first request was sent, in seconds on the browser's monotonic clock
+    pub start_timestamp: f64,
+    /// When loading finished or failed, on the same clock as `start_timestamp`
+    pub end_timestamp: Option<f64>,
+    /// The number of bytes received over the network, including headers, once finished
+    pub encoded_data_length: Option<f64>,
+    pub failure: Option<RequestFailure>,
+}
+
+impl RequestRecord {
+    /// The URL the page originally asked for, before any redirects.
+    pub fn original_url(&self) -> &str {
+        self.redirects
+            .first()
+            .map_or(&self.request.url, |redirect| &redirect.request.url)
+    }
+
+    /// Whether the request finished or failed, rather than still being in flight.
+    pub fn is_complete(&self) -> bool {
+        self.end_timestamp.is_some()
+    }
+
+    /// The final response's status, if there was one.
+    pub fn status(&self) -> Option<u32> {
+        self.response.as_ref().map(|response| response.status)
+    }
+
+    /// How long the request took, including redirects, once it's complete.
+    pub fn duration(&self) -> Option<Duration> {
+        self.end_timestamp
+            .map(|end| Duration::from_secs_f64((end - self.start_timestamp).max(0.0)))
+    }
+}
+
+/// Selects records from a `NetworkLog`. Fields left as `None` match any record.
+#[derive(Clone, Default)]
+pub struct RequestFilter {
+    /// Matched against the final URL
+    pub url: Option<UrlMatcher>,
+    pub method: Option<String>,
+    pub resource_type: Option<Network::ResourceType>,
+    pub status: Option<u32>,
+    pub failed: Option<bool>,
+    pub complete: Option<bool>,
+}
+
+impl RequestFilter {
+    pub fn matches(&self, record: &RequestRecord) -> bool {
+        self.url
+            .as_ref()
+            .is_none_or(|url| url.matches(&record.request.url))
+            && self
+                .method
+                .as_ref()
+                .is_none_or(|method| method.eq_ignore_ascii_case(&record.request.method))
+            && self
+                .resource_type
+                .as_ref()
+                .is_none_or(|resource_type| record.resource_type.as_ref() == Some(resource_type))
+            && self
+                .status
+                .is_none_or(|status| record.status() == Some(status))
+            && self
+                .failed
+                .is_none_or(|failed| record.failure.is_some() == failed)
+            && self
+                .complete
+                .is_none_or(|complete| record.is_complete() == complete)
+    }
+}
+
+/// A log of the requests made by a tab, as returned by `Tab::start_network_log`.
+///
+/// Each request's lifecycle is aggregated into a single `RequestRecord`, in the order the
+/// requests were sent. Only the most recent `NetworkLogOptions::max_records` records are
+/// kept. Clones share the same log.
+#[derive(Clone)]
+pub struct NetworkLog {
+    state: Arc<Mutex<LogState>>,
+}
+
+struct LogState {
+    options: NetworkLogOptions,
+    records: VecDeque<RequestRecord>,
+    /// The sequence number of the front record
+    first_seq: u64,
+    /// Sequence numbers of the requests still in flight
+    in_flight: HashMap<RequestId, u64>,
+    evicted: usize,
+}
+
+impl NetworkLog {
+    pub(crate) fn new(options: NetworkLogOptions) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LogState {
+                options,
+                records: VecDeque::new(),
+                first_seq: 0,
+                in_flight: HashMap::new(),
+                evicted: 0,
+            })),
+        }
+    }
+
+    /// All the records currently kept, oldest first.
+    pub fn records(&self) -> Vec<RequestRecord> {
+        self.state.lock().unwrap().records.iter().cloned().collect()
+    }
+
+    /// The records matching `filter`, oldest first.
+    pub fn filter(&self, filter: &RequestFilter) -> Vec<RequestRecord> {
+        self.state
+            .lock()
+            .unwrap()
+            .records
+            .iter()
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect()
+    }
+
+    /// The most recent record matching `filter`.
+    pub fn find_last(&self, filter: &RequestFilter) -> Option<RequestRecord> {
+        self.state
+            .lock()
+            .unwrap()
+            .records
+            .iter()
+            .rev()
+            .find(|record| filter.matches(record))
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many records have been dropped to stay within `max_records`.
+    pub fn evicted_count(&self) -> usize {
+        self.state.lock().unwrap().evicted
+    }
+
+    /// Forgets all records, including those of requests still in flight.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.first_seq += state.records.len() as u64;
+        state.records.clear();
+        state.in_flight.clear();
+    }
+
+    pub(crate) fn on_event(&self, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            Event::NetworkRequestWillBeSent(ev) => state.on_request(&ev.params),
+            Event::NetworkResponseReceived(ev) => {
+                if let Some(record) = state.in_flight_record(&ev.params.request_id) {
+                    record.response = Some(ev.params.response.clone());
+                }
+            }
+            Event::NetworkLoadingFinished(ev) => {
+                if let Some(record) = state.complete(&ev.params.request_id) {
+                    record.end_timestamp = Some(ev.params.timestamp);
+                    record.encoded_data_length = Some(ev.params.encoded_data_length);
+                }
+            }
+            Event::NetworkLoadingFailed(ev) => state.on_failure(&ev.params),
+            _ => {}
+        }
+    }
+}
+
+impl LogState {
+    fn on_request(&mut self, params: &RequestWillBeSentEventParams) {
+        // a redirect reuses the request ID, so it continues the same record
+        if let Some(redirect_response) = &params.redirect_response {
+            if let Some(record) = self.in_flight_record(&params.request_id) {
+                let previous = std::mem::replace(&mut record.request, params.request.clone());
+                record.redirects.push(Redirect {
+                    request: previous,
+                    response: redirect_response.clone(),
+                });
+                record.response = None;
+                return;
+            }
+        }
+
+        let seq = self.first_seq + self.records.len() as u64;
+        self.in_flight.insert(params.request_id.clone(), seq);
+        self.records.push_back(RequestRecord {
+            request_id: params.request_id.clone(),
+            request: params.request.clone(),
+            redirects: vec![],
+            resource_type: params.Type.clone(),
+            initiator: params.initiator.clone(),
+            response: None,
+            wall_time: params.wall_time,
+            start_timestamp: params.timestamp,
+            end_timestamp: None,
+            encoded_data_length: None,
+            failure: None,
+        });
+
+        while self.records.len() > self.options.max_records {
+            if let Some(evicted) = self.records.pop_front() {
+                if self.in_flight.get(&evicted.request_id) == Some(&self.first_seq) {
+                    self.in_flight.remove(&evicted.request_id);
+                }
+                self.first_seq += 1;
+                self.evicted += 1;
+            }
+        }
+    }
+
+    fn on_failure(&mut self, params: &LoadingFailedEventParams) {
+        if let Some(record) = self.complete(&params.request_id) {
+            record.end_timestamp = Some(params.timestamp);
+            record.failure = Some(RequestFailure {
+                error_text: params.error_text.clone(),
+                canceled: params.canceled.unwrap_or(false),
+                blocked_reason: params.blocked_reason.clone(),
+            });
+        }
+    }
+
+    fn in_flight_record(&mut self, request_id: &RequestId) -> Option<&mut RequestRecord> {
+        let seq = *self.in_flight.get(request_id)?;
+        self.record(seq)
+    }
+
+    /// The record of a request which just finished or failed.
+    fn complete(&mut self, request_id: &RequestId) -> Option<&mut RequestRecord> {
+        let seq = self.in_flight.remove(request_id)?;
+        self.record(seq)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn record(&mut self, seq: u64) -> Option<&mut RequestRecord> {
+        let index = seq.checked_sub(self.first_seq)? as usize;
+        self.records.get_mut(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request_will_be_sent(id: &str, url: &str, redirect_status: Option<u32>) -> Event {
+        let mut params = json!({
+            "requestId": id,
+            "loaderId": "loader",
+            "documentURL": url,
+            "request": {
+                "url": url,
+                "method": "GET",
+                "headers": {},
+                "initialPriority": "High",
+                "referrerPolicy": "no-referrer",
+            },
+            "timestamp": 1.0,
+            "wallTime": 1_700_000_000.0,
+            "initiator": { "type": "script" },
+            "redirectHasExtraInfo": false,
+            "type": "Fetch",
+        });
+        if let Some(status) = redirect_status {
+            params["redirectResponse"] = json!({
+                "url": "https://example.com/old",
+                "status": status,
+                "statusText": "Found",
+                "headers": {},
+                "mimeType": "",
+                "charset": "",
+                "connectionReused": false,
+                "connectionId": 0.0,
+                "encodedDataLength": 0.0,
+                "securityState": "unknown",
+            });
+        }
+        Event::NetworkRequestWillBeSent(
+            serde_json::from_value(json!({
+                "method": "Network.requestWillBeSent",
+                "params": params,
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn aggregates_redirects_and_evicts_oldest_records() {
+        let log = NetworkLog::new(NetworkLogOptions { max_records: 2 });
+        log.on_event(&request_will_be_sent("1", "https://example.com/old", None));
+        log.on_event(&request_will_be_sent(
+            "1",
+            "https://example.com/new",
+            Some(302),
+        ));
+        let records = log.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].request.url, "https://example.com/new");
+        assert_eq!(records[0].original_url(), "https://example.com/old");
+        assert_eq!(records[0].redirects[0].response.status, 302);
+
+        log.on_event(&request_will_be_sent("2", "https://example.com/2", None));
+        log.on_event(&request_will_be_sent("3", "https://example.com/3", None));
+        let urls: Vec<_> = log
+            .records()
+            .into_iter()
+            .map(|record| record.request.url)
+            .collect();
+        assert_eq!(urls, ["https://example.com/2", "https://example.com/3"]);
+        assert_eq!(log.evicted_count(), 1);
+
+        // a redirect of an evicted request starts a new record
+        log.on_event(&request_will_be_sent(
+            "1",
+            "https://example.com/newer",
+            Some(301),
+        ));
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.evicted_count(), 2);
+
+        log.clear();
+        assert!(log.is_empty());
+        log.on_event(&request_will_be_sent("4", "https://example.com/4", None));
+        assert_eq!(log.records()[0].request.url, "https://example.com/4");
+    }
+}
@@ -36,3 +36,14 @@ impl std::ops::Div<f64> for Point {
         }
     }
 }
+
+impl std::ops::Mul<f64> for Point {
+    type Output = Self;
+
+    fn mul(self, other: f64) -> Self {
+        Self {
+            x: self.x * other,
+            y: self.y * other,
+        }
+    }
+}
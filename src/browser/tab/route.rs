@@ -0,0 +1,395 @@
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+
+use thiserror::Error;
+
+use log::{debug, warn};
+
+use base64::Engine;
+
+use super::network::UrlMatcher;
+use crate::browser::transport::{SessionId, Transport};
+use crate::protocol::cdp::Fetch::{
+    ContinueRequest, ContinueResponse, FailRequest, FulfillRequest, GetResponseBody, HeaderEntry,
+    RequestPattern, RequestStage, events::RequestPausedEventParams,
+};
+use crate::protocol::cdp::Network;
+use crate::protocol::cdp::types::Method;
+
+pub(crate) type RouteHandler = dyn Fn(&Route) -> Result<()> + Send + Sync;
+pub(crate) type ResponseRouteHandler = dyn Fn(&ResponseRoute) -> Result<()> + Send + Sync;
+
+/// Whether a route handles requests (`Tab::route`) or responses (`Tab::route_response`).
+#[derive(Clone)]
+pub(crate) enum Handler {
+    Request(Arc<RouteHandler>),
+    Response(Arc<ResponseRouteHandler>),
+}
+
+/// Identifies a route added with `Tab::route`, `Tab::route_response` or
+/// `Tab::route_websocket`, for `Tab::unroute`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteId(usize);
+
+static ROUTE_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl RouteId {
+    pub(crate) fn next() -> Self {
+        Self(ROUTE_ID_COUNTER.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct RegisteredRoute {
+    pub id: RouteId,
+    pub matcher: UrlMatcher,
+    pub handler: Handler,
+}
+
+impl RegisteredRoute {
+    /// The pattern passed to `Fetch.enable` so that only requests this route may be interested
+    /// in are paused.
+    pub fn fetch_pattern(&self) -> RequestPattern {
+        RequestPattern {
+            url_pattern: Some(self.matcher.fetch_url_pattern()),
+            resource_Type: None,
+            request_stage: Some(match self.handler {
+                Handler::Request(_) => RequestStage::Request,
+                Handler::Response(_) => RequestStage::Response,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Route for {url} was already handled")]
+pub struct RouteAlreadyHandled {
+    pub url: String,
+}
+
+#[derive(Debug, Error)]
+#[error("Request to {url} failed before getting a response")]
+pub struct NoUpstreamResponse {
+    pub url: String,
+}
+
+/// Changes to make to a request before sending it on to the network, see
+/// `Route::continue_with`. Fields left as `None` keep the request's original values.
+#[derive(Debug, Clone, Default)]
+pub struct ContinueOverrides {
+    pub url: Option<String>,
+    pub method: Option<String>,
+    pub post_data: Option<Vec<u8>>,
+    /// Replaces all of the request's headers
+    pub headers: Option<Vec<(String, String)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteState {
+    Pending,
+    FellBack,
+    Handled,
+}
+
+/// A request paused by a route added with `Tab::route`.
+///
+/// A route handler should either answer the request with `fulfill`, send it on with
+/// `continue_with`, fail it with `abort`, or leave it to the next matching route with
+/// `fallback`. Returning without doing any of these is the same as calling `fallback`.
+pub struct Route {
+    pub params: RequestPausedEventParams,
+    transport: Arc<Transport>,
+    session_id: SessionId,
+    state: Cell<RouteState>,
+}
+
+impl Debug for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "Route {} {}",
+            self.params.request.method, self.params.request.url
+        )
+    }
+}
+
+impl Route {
+    fn new(
+        params: RequestPausedEventParams,
+        transport: Arc<Transport>,
+        session_id: SessionId,
+    ) -> Self {
+        Self {
+            params,
+            transport,
+            session_id,
+            state: Cell::new(RouteState::Pending),
+        }
+    }
+
+    pub fn request(&self) -> &Network::Request {
+        &self.params.request
+    }
+
+    pub fn resource_type(&self) -> &Network::ResourceType {
+        &self.params.resource_Type
+    }
+
+    /// Answers the request with the given response, without it ever reaching the network.
+    pub fn fulfill<B: AsRef<[u8]>>(
+        &self,
+        status: u32,
+        headers: &[(&str, &str)],
+        body: B,
+    ) -> Result<()> {
+        self.resolve(FulfillRequest {
+            request_id: self.params.request_id.clone(),
+            response_code: status,
+            response_headers: Some(header_entries(
+                headers.iter().map(|(name, value)| (*name, *value)),
+            )),
+            binary_response_headers: None,
+            body: Some(base64::prelude::BASE64_STANDARD.encode(body)),
+            response_phrase: None,
+        })
+    }
+
+    /// Sends the request on to the network, changed according to `overrides`.
+    pub fn continue_with(&self, overrides: ContinueOverrides) -> Result<()> {
+        self.resolve(ContinueRequest {
+            request_id: self.params.request_id.clone(),
+            url: overrides.url,
+            method: overrides.method,
+            post_data: overrides
+                .post_data
+                .map(|data| base64::prelude::BASE64_STANDARD.encode(data)),
+            headers: overrides.headers.map(|headers| {
+                header_entries(
+                    headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_str())),
+                )
+            }),
+            intercept_response: None,
+        })
+    }
+
+    /// Fails the request as if the network had, e.g. with `ErrorReason::BlockedByClient`.
+    pub fn abort(&self, reason: Network::ErrorReason) -> Result<()> {
+        self.resolve(FailRequest {
+            request_id: self.params.request_id.clone(),
+            error_reason: reason,
+        })
+    }
+
+    /// Leaves the request to the next matching route, or, if there is none, to the tab's
+    /// `RequestInterceptor`.
+    pub fn fallback(&self) -> Result<()> {
+        if self.state.get() == RouteState::Handled {
+            return Err(self.already_handled());
+        }
+        self.state.set(RouteState::FellBack);
+        Ok(())
+    }
+
+    /// Resolves the paused request with `method`. The route only counts as handled once the
+    /// browser has accepted it; if it hasn't, the request is still paused and is left to the
+    /// next matching route unless the handler tries again.
+    fn resolve<C>(&self, method: C) -> Result<()>
+    where
+        C: Method + serde::Serialize,
+    {
+        if self.state.get() != RouteState::Pending {
+            return Err(self.already_handled());
+        }
+        self.transport
+            .call_method_on_target(self.session_id.clone(), method)?;
+        self.state.set(RouteState::Handled);
+        Ok(())
+    }
+
+    fn already_handled(&self) -> anyhow::Error {
+        RouteAlreadyHandled {
+            url: self.params.request.url.clone(),
+        }
+        .into()
+    }
+}
+
+/// A response paused by a route added with `Tab::route_response`, before the page gets it.
+///
+/// A handler can read the response's status, headers and body, and then replace it with
+/// `fulfill` or `fulfill_body`, let it through with `continue_response`, fail it with
+/// `abort`, or leave it to the next matching route with `fallback`. Returning without doing
+/// any of these is the same as calling `fallback`.
+pub struct ResponseRoute {
+    route: Route,
+}
+
+impl Debug for ResponseRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "ResponseRoute {:?} {} {}",
+            self.status(),
+            self.route.params.request.method,
+            self.route.params.request.url
+        )
+    }
+}
+
+impl ResponseRoute {
+    pub fn params(&self) -> &RequestPausedEventParams {
+        &self.route.params
+    }
+
+    pub fn request(&self) -> &Network::Request {
+        self.route.request()
+    }
+
+    pub fn resource_type(&self) -> &Network::ResourceType {
+        self.route.resource_type()
+    }
+
+    /// The upstream status code, or `None` if the request failed before getting a response
+    /// (see `params().response_error_reason`)
+    pub fn status(&self) -> Option<u32> {
+        self.route.params.response_status_code
+    }
+
+    pub fn headers(&self) -> &[HeaderEntry] {
+        self.route.params.response_headers.as_deref().unwrap_or(&[])
+    }
+
+    /// The value of the header `name` (case-insensitively), if there is one.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers()
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+
+    /// Fetches the upstream body from the browser.
+    pub fn body(&self) -> Result<Vec<u8>> {
+        let body = self.route.transport.call_method_on_target(
+            self.route.session_id.clone(),
+            GetResponseBody {
+                request_id: self.route.params.request_id.clone(),
+            },
+        )?;
+        if body.base_64_encoded {
+            Ok(base64::prelude::BASE64_STANDARD.decode(body.body)?)
+        } else {
+            Ok(body.body.into_bytes())
+        }
+    }
+
+    /// Fetches the upstream body from the browser as text.
+    pub fn text(&self) -> Result<String> {
+        Ok(String::from_utf8(self.body()?)?)
+    }
+
+    /// Replaces the response with the given one.
+    pub fn fulfill<B: AsRef<[u8]>>(
+        &self,
+        status: u32,
+        headers: &[(&str, &str)],
+        body: B,
+    ) -> Result<()> {
+        self.route.fulfill(status, headers, body)
+    }
+
+    /// Replaces the body of the response, keeping its status and headers (except for the
+    /// ones describing the old body's encoding and length).
+    ///
+    /// Fails with `NoUpstreamResponse` if the request failed before getting a response, as
+    /// there is no status to keep; use `fulfill` instead.
+    pub fn fulfill_body<B: AsRef<[u8]>>(&self, body: B) -> Result<()> {
+        let status = self.status().ok_or_else(|| NoUpstreamResponse {
+            url: self.route.params.request.url.clone(),
+        })?;
+        let headers: Vec<(&str, &str)> = self
+            .headers()
+            .iter()
+            .filter(|header| {
+                !header.name.eq_ignore_ascii_case("content-encoding")
+                    && !header.name.eq_ignore_ascii_case("content-length")
+            })
+            .map(|header| (header.name.as_str(), header.value.as_str()))
+            .collect();
+        self.fulfill(status, &headers, body)
+    }
+
+    /// Lets the response through to the page unchanged.
+    pub fn continue_response(&self) -> Result<()> {
+        self.route.resolve(ContinueResponse {
+            request_id: self.route.params.request_id.clone(),
+            response_code: None,
+            response_phrase: None,
+            response_headers: None,
+            binary_response_headers: None,
+        })
+    }
+
+    /// Fails the request as if the network had.
+    pub fn abort(&self, reason: Network::ErrorReason) -> Result<()> {
+        self.route.abort(reason)
+    }
+
+    /// Leaves the response to the next matching route, or, if there is none, to the tab's
+    /// `RequestInterceptor`.
+    pub fn fallback(&self) -> Result<()> {
+        self.route.fallback()
+    }
+}
+
+fn header_entries<'a>(headers: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<HeaderEntry> {
+    headers
+        .map(|(name, value)| HeaderEntry {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+/// Offers a paused request (or response) to the matching routes, most recently added first,
+/// and returns whether one of them handled it.
+pub(crate) fn dispatch(
+    routes: &[RegisteredRoute],
+    params: &RequestPausedEventParams,
+    transport: &Arc<Transport>,
+    session_id: &SessionId,
+) -> bool {
+    let is_response =
+        params.response_status_code.is_some() || params.response_error_reason.is_some();
+    for registered in routes
+        .iter()
+        .rev()
+        .filter(|registered| registered.matcher.matches(&params.request.url))
+    {
+        let route = Route::new(params.clone(), Arc::clone(transport), session_id.clone());
+        let (result, route) = match &registered.handler {
+            Handler::Request(handler) if !is_response => (handler(&route), route),
+            Handler::Response(handler) if is_response => {
+                let response_route = ResponseRoute { route };
+                (handler(&response_route), response_route.route)
+            }
+            _ => continue,
+        };
+        if let Err(error) = result {
+            warn!("Route handler for {} failed: {error}", params.request.url);
+        }
+        if route.state.get() == RouteState::Handled {
+            debug!(
+                "Request to {} handled by {:?}",
+                params.request.url, registered.id
+            );
+            return true;
+        }
+    }
+    false
+}
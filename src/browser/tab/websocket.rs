@@ -0,0 +1,675 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+
+use log::{debug, warn};
+
+use serde::Deserialize;
+use serde_json::Value as Json;
+
+use super::network::UrlMatcher;
+use super::route::RouteId;
+use crate::browser::transport::{SessionId, Transport};
+use crate::protocol::cdp::Network::{self, RequestId};
+use crate::protocol::cdp::Runtime;
+use crate::protocol::cdp::types::Event;
+use crate::util::Wait;
+
+type FrameListener = dyn Fn(&WebSocketFrame) + Send + Sync;
+type CloseListener = dyn Fn() + Send + Sync;
+pub(crate) type WebSocketHandler = dyn Fn(&WebSocket) + Send + Sync;
+
+/// Whether a frame was sent by the page or received from the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+/// A single WebSocket frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebSocketFrame {
+    pub direction: FrameDirection,
+    /// 1 for text frames, 2 for binary ones
+    pub opcode: u8,
+    /// The text of a text frame, or the base64 encoded data of a binary one
+    pub payload: String,
+    /// In seconds on the browser's monotonic clock
+    pub timestamp: f64,
+}
+
+impl WebSocketFrame {
+    pub fn is_text(&self) -> bool {
+        self.opcode == 1
+    }
+}
+
+/// A WebSocket opened by the page, as passed to the handler of `Tab::on_websocket`.
+///
+/// The socket is updated as its handshake completes and frames go back and forth; clones
+/// share the same state.
+#[derive(Clone)]
+pub struct WebSocket {
+    state: Arc<Mutex<WebSocketState>>,
+}
+
+struct WebSocketState {
+    request_id: RequestId,
+    url: String,
+    initiator: Option<Network::Initiator>,
+    handshake_request: Option<Network::WebSocketRequest>,
+    handshake_response: Option<Network::WebSocketResponse>,
+    frames: Vec<WebSocketFrame>,
+    errors: Vec<String>,
+    closed: bool,
+    frame_listeners: Vec<Arc<FrameListener>>,
+    close_listeners: Vec<Arc<CloseListener>>,
+}
+
+impl Debug for WebSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WebSocket {}", self.url())
+    }
+}
+
+impl WebSocket {
+    fn new(request_id: RequestId, url: String, initiator: Option<Network::Initiator>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(WebSocketState {
+                request_id,
+                url,
+                initiator,
+                handshake_request: None,
+                handshake_response: None,
+                frames: vec![],
+                errors: vec![],
+                closed: false,
+                frame_listeners: vec![],
+                close_listeners: vec![],
+            })),
+        }
+    }
+
+    pub fn request_id(&self) -> RequestId {
+        self.state.lock().unwrap().request_id.clone()
+    }
+
+    pub fn url(&self) -> String {
+        self.state.lock().unwrap().url.clone()
+    }
+
+    pub fn initiator(&self) -> Option<Network::Initiator> {
+        self.state.lock().unwrap().initiator.clone()
+    }
+
+    /// The upgrade request, once it's been sent.
+    pub fn handshake_request(&self) -> Option<Network::WebSocketRequest> {
+        self.state.lock().unwrap().handshake_request.clone()
+    }
+
+    /// The server's answer to the upgrade request, once it's arrived.
+    pub fn handshake_response(&self) -> Option<Network::WebSocketResponse> {
+        self.state.lock().unwrap().handshake_response.clone()
+    }
+
+    /// The frames sent and received so far, oldest first.
+    pub fn frames(&self) -> Vec<WebSocketFrame> {
+        self.state.lock().unwrap().frames.clone()
+    }
+
+    /// The errors Chrome reported for the socket's frames.
+    pub fn errors(&self) -> Vec<String> {
+        self.state.lock().unwrap().errors.clone()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Calls `listener` for every frame sent or received from now on.
+    pub fn on_frame<F: Fn(&WebSocketFrame) + Send + Sync + 'static>(&self, listener: F) {
+        self.state
+            .lock()
+            .unwrap()
+            .frame_listeners
+            .push(Arc::new(listener));
+    }
+
+    /// Calls `listener` when the socket is closed.
+    pub fn on_close<F: Fn() + Send + Sync + 'static>(&self, listener: F) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_listeners
+            .push(Arc::new(listener));
+    }
+
+    /// Blocks until a frame matching `predicate` has been sent or received, including frames
+    /// from before the call, and returns the first one.
+    pub fn wait_for_frame<P>(&self, predicate: P) -> Result<WebSocketFrame>
+    where
+        P: Fn(&WebSocketFrame) -> bool,
+    {
+        self.wait_for_frame_with_custom_timeout(predicate, Duration::from_secs(10))
+    }
+
+    pub fn wait_for_frame_with_custom_timeout<P>(
+        &self,
+        predicate: P,
+        timeout: Duration,
+    ) -> Result<WebSocketFrame>
+    where
+        P: Fn(&WebSocketFrame) -> bool,
+    {
+        Ok(Wait::with_timeout(timeout).until(|| {
+            self.state
+                .lock()
+                .unwrap()
+                .frames
+                .iter()
+                .find(|frame| predicate(frame))
+                .cloned()
+        })?)
+    }
+
+    fn add_frame(
+        &self,
+        direction: FrameDirection,
+        frame: &Network::WebSocketFrame,
+        timestamp: f64,
+    ) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frame = WebSocketFrame {
+            direction,
+            opcode: frame.opcode as u8,
+            payload: frame.payload_data.clone(),
+            timestamp,
+        };
+        let listeners = {
+            let mut state = self.state.lock().unwrap();
+            state.frames.push(frame.clone());
+            state.frame_listeners.clone()
+        };
+        for listener in listeners {
+            listener(&frame);
+        }
+    }
+
+    fn close(&self) {
+        let listeners = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            state.close_listeners.clone()
+        };
+        for listener in listeners {
+            listener();
+        }
+    }
+}
+
+/// Follows the sockets of a tab for `Tab::on_websocket`.
+pub(crate) struct WebSocketTracker {
+    handler: Box<WebSocketHandler>,
+    sockets: Mutex<HashMap<RequestId, WebSocket>>,
+}
+
+impl WebSocketTracker {
+    pub fn new(handler: Box<WebSocketHandler>) -> Self {
+        Self {
+            handler,
+            sockets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn on_event(&self, event: &Event) {
+        let socket = |request_id: &RequestId| self.sockets.lock().unwrap().get(request_id).cloned();
+        match event {
+            Event::NetworkWebSocketCreated(ev) => {
+                let socket = WebSocket::new(
+                    ev.params.request_id.clone(),
+                    ev.params.url.clone(),
+                    ev.params.initiator.clone(),
+                );
+                self.sockets
+                    .lock()
+                    .unwrap()
+                    .insert(ev.params.request_id.clone(), socket.clone());
+                (self.handler)(&socket);
+            }
+            Event::NetworkWebSocketWillSendHandshakeRequest(ev) => {
+                if let Some(socket) = socket(&ev.params.request_id) {
+                    socket.state.lock().unwrap().handshake_request =
+                        Some(ev.params.request.clone());
+                }
+            }
+            Event::NetworkWebSocketHandshakeResponseReceived(ev) => {
+                if let Some(socket) = socket(&ev.params.request_id) {
+                    socket.state.lock().unwrap().handshake_response =
+                        Some(ev.params.response.clone());
+                }
+            }
+            Event::NetworkWebSocketFrameSent(ev) => {
+                if let Some(socket) = socket(&ev.params.request_id) {
+                    socket.add_frame(
+                        FrameDirection::Sent,
+                        &ev.params.response,
+                        ev.params.timestamp,
+                    );
+                }
+            }
+            Event::NetworkWebSocketFrameReceived(ev) => {
+                if let Some(socket) = socket(&ev.params.request_id) {
+                    socket.add_frame(
+                        FrameDirection::Received,
+                        &ev.params.response,
+                        ev.params.timestamp,
+                    );
+                }
+            }
+            Event::NetworkWebSocketFrameError(ev) => {
+                if let Some(socket) = socket(&ev.params.request_id) {
+                    socket
+                        .state
+                        .lock()
+                        .unwrap()
+                        .errors
+                        .push(ev.params.error_message.clone());
+                }
+            }
+            Event::NetworkWebSocketClosed(ev) => {
+                let socket = self.sockets.lock().unwrap().remove(&ev.params.request_id);
+                if let Some(socket) = socket {
+                    socket.close();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The name of the binding through which the mock sockets talk to `WebSocketMocks`.
+pub(crate) const BINDING_NAME: &str = "__headlessChromeWebSocket";
+
+/// Replaces `window.WebSocket` in the top-level document with a class which asks
+/// `WebSocketMocks` whether to mock each socket, and otherwise forwards everything to a real
+/// `WebSocket`. Sockets are identified by a random ID of the document and a counter, so that
+/// calls meant for the sockets of a previous document are ignored.
+pub(crate) const MOCK_SCRIPT: &str = r"
+(() => {
+    if (window !== window.top || window.__headlessChromeWebSockets) {
+        return;
+    }
+    const binding = window.__headlessChromeWebSocket;
+    const NativeWebSocket = window.WebSocket;
+    const documentId = Math.random().toString(36).slice(2) + Date.now().toString(36);
+    const sockets = new Map();
+    const find = (document, id) => document === documentId ? sockets.get(id) : undefined;
+    const notify = message => binding(JSON.stringify({ document: documentId, ...message }));
+    let nextId = 0;
+
+    class WebSocket extends EventTarget {
+        constructor(url, protocols) {
+            super();
+            this.url = new URL(url, location.href).href;
+            this.protocols = protocols;
+            this.binaryType = 'blob';
+            this.onopen = this.onmessage = this.onerror = this.onclose = null;
+            this._id = ++nextId;
+            this._state = 0;
+            this._native = null;
+            sockets.set(this._id, this);
+            notify({ type: 'connect', id: this._id, url: this.url });
+        }
+        get readyState() { return this._native ? this._native.readyState : this._state; }
+        get protocol() { return this._native ? this._native.protocol : ''; }
+        get extensions() { return this._native ? this._native.extensions : ''; }
+        get bufferedAmount() { return this._native ? this._native.bufferedAmount : 0; }
+        send(data) {
+            if (this._native) {
+                return this._native.send(data);
+            }
+            if (this._state === 0) {
+                throw new DOMException('Still in CONNECTING state.', 'InvalidStateError');
+            }
+            if (this._state === 1) {
+                notify({ type: 'message', id: this._id, data: String(data) });
+            }
+        }
+        close(code, reason) {
+            if (this._native) {
+                return this._native.close(code, reason);
+            }
+            if (this._state < 2) {
+                notify({ type: 'close', id: this._id });
+                this._close(code === undefined ? 1005 : code, reason || '');
+            }
+        }
+        _dispatch(event) {
+            this.dispatchEvent(event);
+            const handler = this['on' + event.type];
+            if (handler) {
+                handler.call(this, event);
+            }
+        }
+        _close(code, reason) {
+            this._state = 3;
+            sockets.delete(this._id);
+            this._dispatch(new CloseEvent('close', { code, reason, wasClean: true }));
+        }
+    }
+    for (const [name, value] of Object.entries({ CONNECTING: 0, OPEN: 1, CLOSING: 2, CLOSED: 3 })) {
+        WebSocket[name] = value;
+        WebSocket.prototype[name] = value;
+    }
+
+    window.__headlessChromeWebSockets = {
+        open(document, id) {
+            const socket = find(document, id);
+            if (socket && socket._state === 0) {
+                socket._state = 1;
+                socket._dispatch(new Event('open'));
+            }
+        },
+        message(document, id, data) {
+            const socket = find(document, id);
+            if (socket && socket._state === 1) {
+                socket._dispatch(new MessageEvent('message', { data, origin: new URL(socket.url).origin }));
+            }
+        },
+        close(document, id, code, reason) {
+            const socket = find(document, id);
+            if (socket && socket._state < 2) {
+                socket._close(code, reason);
+            }
+        },
+        passthrough(document, id) {
+            const socket = find(document, id);
+            if (!socket) {
+                return;
+            }
+            sockets.delete(id);
+            const native = new NativeWebSocket(socket.url, socket.protocols);
+            native.binaryType = socket.binaryType;
+            Object.defineProperty(socket, 'binaryType', {
+                get: () => native.binaryType,
+                set: value => { native.binaryType = value; },
+            });
+            socket._native = native;
+            native.onopen = () => socket._dispatch(new Event('open'));
+            native.onerror = () => socket._dispatch(new Event('error'));
+            native.onmessage = event => socket._dispatch(new MessageEvent('message', event));
+            native.onclose = event => socket._dispatch(new CloseEvent('close', event));
+        },
+    };
+    window.WebSocket = WebSocket;
+})()
+";
+
+type WebSocketRouteHandler = dyn Fn(&WebSocketRoute) -> Result<()> + Send + Sync;
+type MessageListener = dyn Fn(&str) + Send + Sync;
+
+/// A route added with `Tab::route_websocket`.
+#[derive(Clone)]
+struct RegisteredWebSocketRoute {
+    id: RouteId,
+    matcher: UrlMatcher,
+    handler: Arc<WebSocketRouteHandler>,
+}
+
+/// A message from `MOCK_SCRIPT` about the socket `id` of the document `document`.
+#[derive(Debug, Deserialize)]
+struct MockMessage {
+    document: String,
+    id: u64,
+    #[serde(flatten)]
+    event: MockEvent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MockEvent {
+    Connect { url: String },
+    Message { data: String },
+    Close,
+}
+
+/// Identifies a mocked socket by the ID `MOCK_SCRIPT` gave its document and its own ID.
+type SocketKey = (String, u64);
+
+/// The WebSocket routes of a tab, and the mocked sockets which are currently open.
+pub(crate) struct WebSocketMocks {
+    transport: Arc<Transport>,
+    session_id: SessionId,
+    routes: Mutex<Vec<RegisteredWebSocketRoute>>,
+    sockets: Mutex<HashMap<SocketKey, WebSocketRoute>>,
+}
+
+impl WebSocketMocks {
+    pub fn new(transport: Arc<Transport>, session_id: SessionId) -> Self {
+        Self {
+            transport,
+            session_id,
+            routes: Mutex::new(vec![]),
+            sockets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn add_route(&self, id: RouteId, matcher: UrlMatcher, handler: Arc<WebSocketRouteHandler>) {
+        self.routes.lock().unwrap().push(RegisteredWebSocketRoute {
+            id,
+            matcher,
+            handler,
+        });
+    }
+
+    /// Removes the route `id`, returning whether there was one. Sockets it already mocks stay
+    /// open.
+    pub fn remove_route(&self, id: RouteId) -> bool {
+        let mut routes = self.routes.lock().unwrap();
+        let count = routes.len();
+        routes.retain(|route| route.id != id);
+        routes.len() != count
+    }
+
+    pub fn clear_routes(&self) {
+        self.routes.lock().unwrap().clear();
+    }
+
+    /// Forgets the mocked sockets of the previous document once the page navigated away from
+    /// it, calling their close listeners.
+    pub fn on_navigated(&self) {
+        let sockets: Vec<WebSocketRoute> = self
+            .sockets
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_key, socket)| socket)
+            .collect();
+        for socket in sockets {
+            socket.closed();
+        }
+    }
+
+    /// Handles a call of the binding by `MOCK_SCRIPT`.
+    pub fn on_binding_called(&self, payload: &Json) {
+        let Some(Ok(MockMessage {
+            document,
+            id,
+            event,
+        })) = payload.as_str().map(serde_json::from_str::<MockMessage>)
+        else {
+            warn!("Unexpected message from mocked WebSocket: {payload}");
+            return;
+        };
+        let key = (document, id);
+        match event {
+            MockEvent::Connect { url } => self.connect(key, url),
+            MockEvent::Message { data } => {
+                let socket = self.sockets.lock().unwrap().get(&key).cloned();
+                if let Some(socket) = socket {
+                    let listeners = socket.state.lock().unwrap().message_listeners.clone();
+                    for listener in listeners {
+                        listener(&data);
+                    }
+                }
+            }
+            MockEvent::Close => {
+                let socket = self.sockets.lock().unwrap().remove(&key);
+                if let Some(socket) = socket {
+                    socket.closed();
+                }
+            }
+        }
+    }
+
+    fn connect(&self, key: SocketKey, url: String) {
+        let route = self
+            .routes
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|route| route.matcher.matches(&url))
+            .cloned();
+        let socket = WebSocketRoute {
+            document: key.0.clone(),
+            id: key.1,
+            url,
+            transport: Arc::clone(&self.transport),
+            session_id: self.session_id.clone(),
+            state: Arc::new(Mutex::new(MockState {
+                message_listeners: vec![],
+                close_listeners: vec![],
+            })),
+        };
+        let Some(route) = route else {
+            debug!("Letting WebSocket to {} through", socket.url);
+            if let Err(error) = socket.call_mock("passthrough", "") {
+                warn!("Failed to connect WebSocket to {}: {error}", socket.url);
+            }
+            return;
+        };
+
+        debug!("Mocking WebSocket to {} with {:?}", socket.url, route.id);
+        self.sockets.lock().unwrap().insert(key, socket.clone());
+        // messages the page sends on open are only handled after the route handler returned,
+        // so it can still add its listeners
+        let opened = socket.call_mock("open", "");
+        if let Err(error) = opened.and_then(|()| (route.handler)(&socket)) {
+            warn!("WebSocket route handler for {} failed: {error}", socket.url);
+        }
+    }
+}
+
+struct MockState {
+    message_listeners: Vec<Arc<MessageListener>>,
+    close_listeners: Vec<Arc<CloseListener>>,
+}
+
+/// A mocked WebSocket, as passed to the handler of `Tab::route_websocket`. It plays the part
+/// of the server: the page's messages arrive at `on_message`, and `send` delivers messages to
+/// the page. Clones refer to the same socket.
+#[derive(Clone)]
+pub struct WebSocketRoute {
+    document: String,
+    id: u64,
+    url: String,
+    transport: Arc<Transport>,
+    session_id: SessionId,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Debug for WebSocketRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WebSocketRoute {}", self.url)
+    }
+}
+
+impl WebSocketRoute {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Sends a text message to the page, as if the server had. Does nothing once the page
+    /// navigated away.
+    pub fn send(&self, message: &str) -> Result<()> {
+        self.call_mock("message", &format!(", {}", serde_json::to_string(message)?))
+    }
+
+    /// Closes the socket, as if the server had. Does nothing once the page navigated away.
+    pub fn close(&self, code: u16, reason: &str) -> Result<()> {
+        self.call_mock(
+            "close",
+            &format!(", {code}, {}", serde_json::to_string(reason)?),
+        )
+    }
+
+    /// Calls `listener` with every message the page sends. Binary messages aren't supported.
+    pub fn on_message<F: Fn(&str) + Send + Sync + 'static>(&self, listener: F) {
+        self.state
+            .lock()
+            .unwrap()
+            .message_listeners
+            .push(Arc::new(listener));
+    }
+
+    /// Calls `listener` when the page closes the socket or navigates away.
+    pub fn on_close<F: Fn() + Send + Sync + 'static>(&self, listener: F) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_listeners
+            .push(Arc::new(listener));
+    }
+
+    fn closed(&self) {
+        let listeners = self.state.lock().unwrap().close_listeners.clone();
+        for listener in listeners {
+            listener();
+        }
+    }
+
+    /// Calls `function` of `MOCK_SCRIPT` for this socket, followed by `extra_args`.
+    fn call_mock(&self, function: &str, extra_args: &str) -> Result<()> {
+        evaluate(
+            &self.transport,
+            &self.session_id,
+            &format!(
+                "window.__headlessChromeWebSockets.{function}({}, {}{extra_args})",
+                serde_json::to_string(&self.document)?,
+                self.id
+            ),
+        )
+    }
+}
+
+fn evaluate(transport: &Transport, session_id: &SessionId, expression: &str) -> Result<()> {
+    let result = transport.call_method_on_target(
+        session_id.clone(),
+        Runtime::Evaluate {
+            expression: expression.to_string(),
+            return_by_value: Some(true),
+            generate_preview: None,
+            silent: Some(true),
+            await_promise: None,
+            include_command_line_api: None,
+            user_gesture: None,
+            object_group: None,
+            context_id: None,
+            throw_on_side_effect: None,
+            timeout: None,
+            disable_breaks: None,
+            repl_mode: None,
+            allow_unsafe_eval_blocked_by_csp: None,
+            unique_context_id: None,
+            serialization_options: None,
+        },
+    )?;
+    if let Some(details) = result.exception_details {
+        return Err(super::js::JsException::from(details).into());
+    }
+    Ok(())
+}
@@ -104,7 +104,10 @@ clippy::should_implement_trait, // for browser/mod.rs:106
 
 pub use browser::{
     Browser, LaunchOptions, LaunchOptionsBuilder,
-    tab::{Tab, element::Element},
+    tab::{
+        Tab,
+        element::{Element, ElementHandle},
+    },
 };
 
 #[cfg(feature = "fetch")]
@@ -0,0 +1,132 @@
+use anyhow::Result;
+
+use headless_chrome::browser::tab::ModifierKey;
+use headless_chrome::browser::tab::keys::KeyboardLayout;
+use headless_chrome::{Browser, Tab};
+
+mod server;
+
+fn take_events(tab: &Tab) -> Result<Vec<String>> {
+    let json = tab
+        .evaluate(
+            "(() => { const events = JSON.stringify(window.events); window.events = []; return events; })()",
+            false,
+        )?
+        .value
+        .unwrap();
+    Ok(serde_json::from_str(json.as_str().unwrap())?)
+}
+
+fn input_value(tab: &Tab) -> Result<String> {
+    Ok(tab
+        .evaluate("document.getElementById('input').value", false)?
+        .value
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string())
+}
+
+#[test]
+fn type_str_presses_shift_for_shifted_characters() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("keyboard.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    tab.wait_for_element("#input")?.click()?;
+
+    tab.type_str("aB")?;
+    assert_eq!(input_value(&tab)?, "aB");
+    assert_eq!(
+        take_events(&tab)?,
+        [
+            "keydown a KeyA ",
+            "keyup a KeyA ",
+            "keydown Shift ShiftLeft Shift",
+            "keydown B KeyB Shift",
+            "keyup B KeyB Shift",
+            "keyup Shift ShiftLeft ",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn type_str_on_german_layout() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("keyboard.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    tab.wait_for_element("#input")?.click()?;
+
+    tab.set_keyboard_layout(KeyboardLayout::german());
+    tab.type_str("zö@")?;
+    assert_eq!(input_value(&tab)?, "zö@");
+
+    let events = take_events(&tab)?;
+    assert_eq!(events[0], "keydown z KeyY ");
+    assert_eq!(events[2], "keydown ö Semicolon ");
+    assert!(events.contains(&"keydown AltGraph AltGraph ".to_string()));
+    assert!(events.contains(&"keydown @ KeyQ ".to_string()));
+    Ok(())
+}
+
+#[test]
+fn chords_and_held_modifiers() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("keyboard.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    tab.wait_for_element("#input")?.click()?;
+
+    tab.press_key("Control+Shift+KeyK")?;
+    assert_eq!(
+        take_events(&tab)?,
+        [
+            "keydown Control ControlLeft Control",
+            "keydown Shift ShiftLeft Control+Shift",
+            "keydown K KeyK Control+Shift",
+            "keyup K KeyK Control+Shift",
+            "keyup Shift ShiftLeft Control",
+            "keyup Control ControlLeft ",
+        ]
+    );
+    assert_eq!(
+        tab.evaluate("window.shortcut", false)?.value,
+        Some(serde_json::Value::Bool(true))
+    );
+    assert_eq!(input_value(&tab)?, "");
+
+    tab.key_down("Shift")?;
+    tab.type_str("x")?;
+    tab.key_up("Shift")?;
+    assert_eq!(input_value(&tab)?, "x");
+
+    tab.press_key_with_modifiers("a", Some(&[ModifierKey::Ctrl]))?;
+    let events = take_events(&tab)?;
+    assert!(events.contains(&"keydown Control ControlLeft Control".to_string()));
+    assert!(events.contains(&"keydown a KeyA Control".to_string()));
+    Ok(())
+}
+
+#[test]
+fn failed_chord_releases_pressed_keys() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("keyboard.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    tab.wait_for_element("#input")?.click()?;
+
+    assert!(tab.press_key("Control+Knotakey").is_err());
+    assert_eq!(
+        take_events(&tab)?,
+        [
+            "keydown Control ControlLeft Control",
+            "keyup Control ControlLeft ",
+        ]
+    );
+
+    tab.type_str("a")?;
+    assert_eq!(input_value(&tab)?, "a");
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use headless_chrome::Browser;
+use headless_chrome::browser::tab::Tab;
+
+mod server;
+
+fn navigate_to_fixture(server: &server::Server) -> Result<(Browser, Arc<Tab>)> {
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    Ok((browser, tab))
+}
+
+#[test]
+fn locator_survives_rerenders_and_waits_until_enabled() -> Result<()> {
+    let server =
+        server::Server::with_dumb_html(include_str!("locator_fixtures/rerendering_page.html"));
+    let (_browser, tab) = navigate_to_fixture(&server)?;
+
+    let save = tab.locator("#app").locator("button");
+    save.click()?;
+    save.click()?;
+
+    assert_eq!(tab.locator("#clicks").text()?, "2");
+    Ok(())
+}
+
+#[test]
+fn locator_count_nth_and_filter() -> Result<()> {
+    let server =
+        server::Server::with_dumb_html(include_str!("locator_fixtures/rerendering_page.html"));
+    let (_browser, tab) = navigate_to_fixture(&server)?;
+
+    let items = tab.locator("#items li");
+    assert_eq!(items.count()?, 3);
+    assert_eq!(items.nth(1).text()?, "banana");
+    assert_eq!(items.last().text()?, "cherry");
+    assert_eq!(items.filter("err").count()?, 1);
+    assert_eq!(tab.locator("#missing").count_now()?, 0);
+    assert!(
+        tab.locator("#missing")
+            .with_timeout(Duration::from_millis(200))
+            .count()
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn locator_fill_replaces_value() -> Result<()> {
+    let server =
+        server::Server::with_dumb_html(include_str!("locator_fixtures/rerendering_page.html"));
+    let (_browser, tab) = navigate_to_fixture(&server)?;
+
+    let input = tab.locator("#name");
+    input.fill("mothership")?;
+
+    let value = input
+        .element()?
+        .call_js_fn("function() { return this.value; }", vec![], false)?
+        .value
+        .unwrap();
+    assert_eq!(value, "mothership");
+    Ok(())
+}
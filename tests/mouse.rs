@@ -0,0 +1,311 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use headless_chrome::Browser;
+use headless_chrome::browser::tab::locator::ElementNotActionable;
+use headless_chrome::browser::tab::point::Point;
+use headless_chrome::browser::tab::{ClickOptions, ModifierKey, Tab};
+use headless_chrome::protocol::cdp::Input::MouseButton;
+
+mod server;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    button: u32,
+    buttons: u32,
+    detail: u32,
+    ctrl_key: bool,
+    shift_key: bool,
+    offset_x: i32,
+    offset_y: i32,
+}
+
+fn take_events(tab: &Tab) -> Result<Vec<RecordedEvent>> {
+    let json = tab
+        .evaluate(
+            "(() => { const events = JSON.stringify(window.events); window.events = []; return events; })()",
+            false,
+        )?
+        .value
+        .unwrap();
+    Ok(serde_json::from_str(json.as_str().unwrap())?)
+}
+
+fn types(events: &[RecordedEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .filter(|e| e.event_type != "mousemove")
+        .map(|e| e.event_type.as_str())
+        .collect()
+}
+
+fn setup(server: &server::Server) -> Result<(Browser, Arc<Tab>)> {
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    Ok((browser, tab))
+}
+
+#[test]
+fn double_right_and_middle_click() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/mouse_events.html"));
+    let (_browser, tab) = setup(&server)?;
+    let target = tab.wait_for_element("#target")?;
+
+    target.double_click()?;
+    let events = take_events(&tab)?;
+    assert_eq!(
+        types(&events),
+        [
+            "mousedown",
+            "mouseup",
+            "click",
+            "mousedown",
+            "mouseup",
+            "click",
+            "dblclick"
+        ]
+    );
+    assert_eq!(events.last().unwrap().detail, 2);
+
+    target.right_click()?;
+    let events = take_events(&tab)?;
+    assert_eq!(types(&events), ["mousedown", "mouseup", "contextmenu"]);
+    assert!(events.iter().all(|e| e.button == 2));
+
+    target.middle_click()?;
+    let events = take_events(&tab)?;
+    assert_eq!(types(&events), ["mousedown", "mouseup", "auxclick"]);
+    assert_eq!(events[0].buttons, 4);
+    Ok(())
+}
+
+#[test]
+fn click_with_modifiers_and_offset() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/mouse_events.html"));
+    let (_browser, tab) = setup(&server)?;
+    let target = tab.wait_for_element("#target")?;
+
+    target.click_with_modifiers(&[ModifierKey::Ctrl, ModifierKey::Shift])?;
+    let events = take_events(&tab)?;
+    let click = events.iter().find(|e| e.event_type == "click").unwrap();
+    assert!(click.ctrl_key && click.shift_key);
+
+    target.click_with_options(&ClickOptions {
+        offset: Some(Point { x: 10.0, y: 15.0 }),
+        ..Default::default()
+    })?;
+    let events = take_events(&tab)?;
+    let click = events.iter().find(|e| e.event_type == "click").unwrap();
+    assert_eq!((click.offset_x, click.offset_y), (10, 15));
+    Ok(())
+}
+
+#[test]
+fn held_buttons_are_reported_while_moving() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/mouse_events.html"));
+    let (_browser, tab) = setup(&server)?;
+    tab.wait_for_element("#target")?;
+
+    tab.move_mouse_to_point(Point { x: 30.0, y: 30.0 })?;
+    tab.mouse_down(MouseButton::Left, None)?;
+    tab.move_mouse_to_point(Point { x: 60.0, y: 60.0 })?;
+    tab.mouse_down(MouseButton::Right, None)?;
+    tab.mouse_up(MouseButton::Left, None)?;
+    tab.move_mouse_to_point(Point { x: 90.0, y: 90.0 })?;
+    tab.mouse_up(MouseButton::Right, None)?;
+
+    let events = take_events(&tab)?;
+    let buttons: Vec<(&str, u32)> = events
+        .iter()
+        .map(|e| (e.event_type.as_str(), e.buttons))
+        .collect();
+    assert_eq!(
+        buttons,
+        [
+            ("mousemove", 0),
+            ("mousedown", 1),
+            ("mousemove", 1),
+            ("mouseup", 2),
+            ("mousemove", 2),
+            ("mouseup", 0),
+            ("contextmenu", 0),
+        ]
+    );
+    Ok(())
+}
+
+fn drag_results(tab: &Tab) -> Result<serde_json::Value> {
+    let json = tab
+        .evaluate("JSON.stringify(window.results)", false)?
+        .value
+        .unwrap();
+    Ok(serde_json::from_str(json.as_str().unwrap())?)
+}
+
+#[test]
+fn drag_with_mouse_events() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/drag_and_drop.html"));
+    let (_browser, tab) = setup(&server)?;
+    tab.wait_for_element("#handle")?;
+
+    tab.drag(Point { x: 70.0, y: 70.0 }, Point { x: 400.0, y: 90.0 }, 5)?;
+    assert_eq!(drag_results(&tab)?["mouse"], serde_json::json!([400, 90]));
+    Ok(())
+}
+
+#[test]
+fn drag_html5_element_with_data_transfer() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/drag_and_drop.html"));
+    let (_browser, tab) = setup(&server)?;
+    let card = tab.wait_for_element("#card")?;
+    let column = tab.wait_for_element("#column")?;
+
+    card.drag_to(&column)?;
+    assert_eq!(drag_results(&tab)?["html5"], "card-1");
+    tab.wait_for_element("#column > #card")?;
+    Ok(())
+}
+
+#[test]
+fn drop_files_onto_drop_zone() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/drag_and_drop.html"));
+    let (_browser, tab) = setup(&server)?;
+    let dropzone = tab.wait_for_element("#dropzone")?;
+
+    let path = std::env::temp_dir().join("headless_chrome_drop_test.txt");
+    std::fs::write(&path, "dropped contents")?;
+    dropzone.drop_files(&[path.to_str().unwrap()])?;
+
+    let file = headless_chrome::util::Wait::default().until(|| {
+        drag_results(&tab)
+            .ok()
+            .and_then(|results| results.get("file").cloned())
+    })?;
+    assert_eq!(file["name"], "headless_chrome_drop_test.txt");
+    assert_eq!(file["text"], "dropped contents");
+    Ok(())
+}
+
+fn evaluate_f64(tab: &Tab, expression: &str) -> Result<f64> {
+    Ok(tab
+        .evaluate(expression, false)?
+        .value
+        .unwrap()
+        .as_f64()
+        .unwrap())
+}
+
+#[test]
+fn scroll_page_and_inner_container() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/scrolling.html"));
+    let (_browser, tab) = setup(&server)?;
+
+    tab.scroll_to(0.0, 300.0)?;
+    assert_eq!(evaluate_f64(&tab, "window.scrollY")?, 300.0);
+    tab.scroll_by(0.0, -100.0)?;
+    assert_eq!(evaluate_f64(&tab, "window.scrollY")?, 200.0);
+    assert!(tab.scroll_by(0.0, f64::INFINITY).is_err());
+    assert!(tab.scroll_to(f64::NAN, 0.0).is_err());
+    assert_eq!(evaluate_f64(&tab, "window.scrollY")?, 200.0);
+
+    tab.scroll_to(0.0, 0.0)?;
+    let inner = tab.wait_for_element("#inner")?;
+    inner.scroll_within(50.0, 120.0)?;
+    assert_eq!(
+        evaluate_f64(&tab, "document.getElementById('inner').scrollTop")?,
+        120.0
+    );
+    assert_eq!(
+        evaluate_f64(&tab, "document.getElementById('inner').scrollLeft")?,
+        50.0
+    );
+    assert_eq!(evaluate_f64(&tab, "window.scrollY")?, 0.0);
+    Ok(())
+}
+
+#[test]
+fn mouse_wheel_scrolls_under_the_pointer() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/scrolling.html"));
+    let (_browser, tab) = setup(&server)?;
+    tab.wait_for_element("#inner")?;
+
+    tab.mouse_wheel_at_point(Point { x: 100.0, y: 100.0 }, 0.0, 150.0)?;
+    headless_chrome::util::Wait::default().until(|| {
+        let scroll_top = evaluate_f64(&tab, "document.getElementById('inner').scrollTop").ok()?;
+        (scroll_top > 0.0).then_some(())
+    })?;
+    assert_eq!(evaluate_f64(&tab, "window.wheelEvents")?, 1.0);
+    Ok(())
+}
+
+#[test]
+fn scroll_until_no_new_content() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/scrolling.html"));
+    let (_browser, tab) = setup(&server)?;
+    tab.wait_for_element("#feed")?;
+
+    let loads = tab.scroll_until_no_new_content(Duration::from_secs(2), None)?;
+    assert_eq!(loads, 3);
+    assert_eq!(tab.find_elements(".item")?.len(), 20);
+
+    tab.scroll_to(0.0, 0.0)?;
+    assert_eq!(
+        tab.scroll_until_no_new_content(Duration::from_millis(500), Some(1))?,
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn click_checks_actionability() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/actionability.html"));
+    let (_browser, tab) = setup(&server)?;
+    let clicked = || -> Result<Option<String>> {
+        Ok(tab
+            .evaluate("window.clicked", false)?
+            .value
+            .and_then(|v| v.as_str().map(ToString::to_string)))
+    };
+
+    let error = tab.wait_for_element("#covered")?.click().unwrap_err();
+    assert!(error.is::<ElementNotActionable>());
+    assert!(
+        error.to_string().contains("div#overlay.modal.backdrop"),
+        "{error}"
+    );
+
+    let error = tab.find_element("#moving")?.click().unwrap_err();
+    assert!(error.to_string().contains("not stable"), "{error}");
+
+    let error = tab.find_element("#disabled")?.click().unwrap_err();
+    assert!(error.to_string().contains("disabled"), "{error}");
+    assert_eq!(clicked()?, None);
+
+    tab.find_element("#clickable")?.click()?;
+    assert_eq!(clicked()?.as_deref(), Some("clickable"));
+    tab.find_element("#through")?.click()?;
+    assert_eq!(clicked()?.as_deref(), Some("through"));
+    Ok(())
+}
+
+#[test]
+fn click_in_background_tab() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("mouse_fixtures/actionability.html"));
+    let (browser, tab) = setup(&server)?;
+    let element = tab.wait_for_element("#clickable")?;
+    browser.new_tab()?.activate()?;
+
+    element.click()?;
+    assert_eq!(
+        tab.evaluate("window.clicked", false)?.value,
+        Some(serde_json::Value::String("clickable".to_string()))
+    );
+    Ok(())
+}
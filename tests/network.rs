@@ -0,0 +1,645 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+
+use headless_chrome::Browser;
+use headless_chrome::browser::context::ContextOptions;
+use headless_chrome::browser::tab::Tab;
+use headless_chrome::browser::tab::har::{Har, HarRecordingOptions, HarReplayOptions};
+use headless_chrome::browser::tab::network::{BlockedRequestCounts, NetworkConditions};
+use headless_chrome::browser::tab::network_log::{NetworkLogOptions, RequestFilter};
+use headless_chrome::browser::tab::route::ContinueOverrides;
+use headless_chrome::protocol::cdp::Network::{ErrorReason, ResourceType};
+use headless_chrome::protocol::cdp::Storage::StorageType;
+use headless_chrome::util::Wait;
+
+mod server;
+
+/// A server answering every request with its method and URL, e.g. `GET /api/user`, except
+/// for `/redirect`, which redirects to `/target`.
+fn echo_server() -> server::Server {
+    server::Server::new(|request: tiny_http::Request| {
+        if request.url() == "/redirect" {
+            let location = tiny_http::Header::from_bytes(&b"Location"[..], &b"/target"[..]);
+            return request.respond(tiny_http::Response::empty(302).with_header(location.unwrap()));
+        }
+        let body = format!("{} {}", request.method(), request.url());
+        let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/plain"[..]);
+        request.respond(tiny_http::Response::from_string(body).with_header(header.unwrap()))
+    })
+}
+
+fn navigate_to_server(server: &server::Server) -> Result<(Browser, Arc<Tab>)> {
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    Ok((browser, tab))
+}
+
+fn fetch_text(tab: &Tab, path: &str) -> Result<String> {
+    tab.call_function(
+        "path => fetch(path).then(response => response.text(), () => 'failed')",
+        (path,),
+    )
+}
+
+#[test]
+fn route_fulfills_continues_and_aborts_requests() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    let mock = tab.route("**/api/*", |route| {
+        route.fulfill(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"mocked":true}"#,
+        )
+    })?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, r#"{"mocked":true}"#);
+    assert_eq!(fetch_text(&tab, "/other")?, "GET /other");
+
+    tab.route("**/moved", |route| {
+        let url = route.request().url.replace("/moved", "/new-home");
+        route.continue_with(ContinueOverrides {
+            url: Some(url),
+            method: Some("POST".to_string()),
+            ..Default::default()
+        })
+    })?;
+    assert_eq!(fetch_text(&tab, "/moved")?, "POST /new-home");
+
+    tab.route("**/ads/**", |route| {
+        route.abort(ErrorReason::BlockedByClient)
+    })?;
+    assert_eq!(fetch_text(&tab, "/ads/banner.js")?, "failed");
+
+    assert!(tab.unroute(mock)?);
+    assert!(!tab.unroute(mock)?);
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user");
+
+    tab.unroute_all()?;
+    assert_eq!(fetch_text(&tab, "/ads/banner.js")?, "GET /ads/banner.js");
+    Ok(())
+}
+
+#[test]
+fn later_routes_run_first_and_can_fall_back() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    tab.route("**/api/**", |route| route.fulfill(200, &[], "general"))?;
+    let inspected = Arc::new(AtomicUsize::new(0));
+    let inspected_clone = Arc::clone(&inspected);
+    tab.route("**/api/**", move |route| {
+        inspected_clone.fetch_add(1, Ordering::SeqCst);
+        if route.request().url.ends_with("/special") {
+            route.fulfill(200, &[], "special")
+        } else {
+            route.fallback()
+        }
+    })?;
+    // a route which doesn't do anything falls back, too
+    tab.route(regex::Regex::new(r"/api/")?, |_route| Ok(()))?;
+
+    assert_eq!(fetch_text(&tab, "/api/special")?, "special");
+    assert_eq!(fetch_text(&tab, "/api/plain")?, "general");
+    assert_eq!(inspected.load(Ordering::SeqCst), 2);
+
+    // handling a route twice fails, and the first answer wins
+    tab.route("**/twice", |route| {
+        route.fulfill(200, &[], "first")?;
+        route.fulfill(200, &[], "second")
+    })?;
+    assert_eq!(fetch_text(&tab, "/twice")?, "first");
+    Ok(())
+}
+
+#[test]
+fn failed_route_answers_leave_the_request_paused() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    // the browser rejects the invalid header, so the request goes on to the network
+    tab.route("**/invalid", |route| {
+        route.fulfill(200, &[("Bad Header\n", "value")], "invalid")
+    })?;
+    assert_eq!(fetch_text(&tab, "/invalid")?, "GET /invalid");
+
+    // and a handler may try again after a failed answer
+    tab.route("**/retried", |route| {
+        route
+            .fulfill(200, &[("Bad Header\n", "value")], "invalid")
+            .or_else(|_| route.fulfill(200, &[], "retried"))
+    })?;
+    assert_eq!(fetch_text(&tab, "/retried")?, "retried");
+    Ok(())
+}
+
+#[test]
+fn records_har_with_redirects_and_bodies() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    tab.start_har_recording_with_options(HarRecordingOptions {
+        capture_content: true,
+    })?;
+    assert_eq!(
+        fetch_text(&tab, "/api/data?id=7&q=a%20b")?,
+        "GET /api/data?id=7&q=a%20b"
+    );
+    assert_eq!(fetch_text(&tab, "/redirect")?, "GET /target");
+    tab.route("**/blocked", |route| route.abort(ErrorReason::Failed))?;
+    assert_eq!(fetch_text(&tab, "/blocked")?, "failed");
+    // loadingFinished may arrive after the page has the body
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let har = tab.stop_har_recording()?;
+    assert!(tab.stop_har_recording().is_err());
+
+    let entries = &har.log.entries;
+    assert_eq!(har.log.version, "1.2");
+    let entry_for = |path: &str| {
+        entries
+            .iter()
+            .find(|entry| entry.request.url == format!("{}{path}", server.url()))
+            .unwrap_or_else(|| panic!("no entry for {path} in {entries:#?}"))
+    };
+
+    let data = entry_for("/api/data?id=7&q=a%20b");
+    assert_eq!(data.request.method, "GET");
+    assert_eq!(data.response.status, 200);
+    assert_eq!(data.response.content.mime_type, "text/plain");
+    assert_eq!(
+        data.response.content.text.as_deref(),
+        Some("GET /api/data?id=7&q=a%20b")
+    );
+    assert_eq!(data.response.content.size, 26);
+    let query: Vec<_> = data
+        .request
+        .query_string
+        .iter()
+        .map(|param| (param.name.as_str(), param.value.as_str()))
+        .collect();
+    assert_eq!(query, [("id", "7"), ("q", "a b")]);
+    assert!(data.time >= 0.0 && data.timings.wait >= 0.0);
+    assert!(data.started_date_time.ends_with('Z'));
+
+    let redirect = entry_for("/redirect");
+    assert_eq!(redirect.response.status, 302);
+    assert_eq!(
+        redirect.response.redirect_url,
+        format!("{}/target", server.url())
+    );
+    assert_eq!(entry_for("/target").response.status, 200);
+
+    let blocked = entry_for("/blocked");
+    assert_eq!(blocked.response.status, 0);
+    assert_eq!(blocked.error.as_deref(), Some("net::ERR_FAILED"));
+
+    assert_eq!(Har::from_json(&har.to_json()?)?, har);
+    Ok(())
+}
+
+#[test]
+fn replays_har_and_records_missing_entries() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+    let dir = tempfile::tempdir()?;
+
+    tab.start_har_recording_with_options(HarRecordingOptions {
+        capture_content: true,
+    })?;
+    fetch_text(&tab, "/api/user")?;
+    fetch_text(&tab, "/redirect")?;
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let mut har = tab.stop_har_recording()?;
+    // make the archive distinguishable from the server
+    for entry in &mut har.log.entries {
+        if let Some(text) = &mut entry.response.content.text {
+            *text = format!("archived {text}");
+        }
+    }
+    let archive_path = dir.path().join("archive.har");
+    har.save(&archive_path)?;
+
+    let replay = tab.route_from_har(&archive_path, HarReplayOptions::default())?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, "archived GET /api/user");
+    assert_eq!(fetch_text(&tab, "/redirect")?, "archived GET /target");
+    assert_eq!(fetch_text(&tab, "/api/unknown")?, "failed");
+    let post: String = tab.call_function(
+        "path => fetch(path, { method: 'POST' }).then(response => response.text(), () => 'failed')",
+        ("/api/user",),
+    )?;
+    assert_eq!(post, "failed");
+    tab.unroute(replay)?;
+
+    let updated_path = dir.path().join("updated.har");
+    let update = tab.route_from_har(
+        &updated_path,
+        HarReplayOptions {
+            url: Some("**/api/**".into()),
+            update: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(fetch_text(&tab, "/api/new")?, "GET /api/new");
+    assert_eq!(fetch_text(&tab, "/other")?, "GET /other");
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    tab.save_har_updates()?;
+    assert_eq!(Har::from_file(&updated_path)?.log.entries.len(), 1);
+    tab.unroute(update)?;
+
+    let updated = Har::from_file(&updated_path)?;
+    let urls: Vec<&str> = updated
+        .log
+        .entries
+        .iter()
+        .map(|entry| entry.request.url.as_str())
+        .collect();
+    assert_eq!(urls, [format!("{}/api/new", server.url())]);
+    assert_eq!(
+        updated.log.entries[0].response.content.text.as_deref(),
+        Some("GET /api/new")
+    );
+    Ok(())
+}
+
+#[test]
+fn emulates_network_conditions() -> Result<()> {
+    let server = echo_server();
+    let (browser, tab) = navigate_to_server(&server)?;
+
+    tab.emulate_network(NetworkConditions::OFFLINE)?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, "failed");
+    assert!(!tab.evaluate_typed::<bool>("navigator.onLine")?);
+
+    tab.emulate_network(NetworkConditions {
+        latency: 500.0,
+        ..Default::default()
+    })?;
+    let start = std::time::Instant::now();
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user");
+    assert!(start.elapsed() >= std::time::Duration::from_millis(500));
+
+    tab.emulate_network(NetworkConditions::default())?;
+    assert!(tab.evaluate_typed::<bool>("navigator.onLine")?);
+
+    let context = browser.new_context()?;
+    let existing = context.new_tab()?;
+    context.emulate_network(NetworkConditions::OFFLINE)?;
+    let opened_later = context.new_tab()?;
+    opened_later.evaluate("window.open('about:blank')", false)?;
+    let popup = Wait::default().until(|| {
+        context.get_tabs().ok()?.into_iter().find(|tab| {
+            *tab.get_target_id() != *existing.get_target_id()
+                && *tab.get_target_id() != *opened_later.get_target_id()
+        })
+    })?;
+    for tab in [existing, opened_later, popup] {
+        assert!(!tab.evaluate_typed::<bool>("navigator.onLine")?);
+    }
+    // other contexts aren't affected
+    assert!(tab.evaluate_typed::<bool>("navigator.onLine")?);
+    Ok(())
+}
+
+#[test]
+fn blocks_urls_and_resource_types() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    tab.block_urls(&["*/ads/*", "*.woff2"])?;
+    assert_eq!(fetch_text(&tab, "/ads/banner.js")?, "failed");
+    assert_eq!(fetch_text(&tab, "/fonts/serif.woff2")?, "failed");
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user");
+    tab.block_urls(&[])?;
+    assert_eq!(fetch_text(&tab, "/ads/banner.js")?, "GET /ads/banner.js");
+
+    tab.block_resource_types(&[ResourceType::Fetch])?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, "failed");
+    let xhr: String = tab.evaluate_typed(
+        "new Promise(resolve => {
+            const xhr = new XMLHttpRequest();
+            xhr.onload = () => resolve(xhr.responseText);
+            xhr.open('GET', '/api/xhr');
+            xhr.send();
+        })",
+    )?;
+    assert_eq!(xhr, "GET /api/xhr");
+    tab.block_resource_types(&[])?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user");
+
+    // URL blocks are counted when Chrome reports the failure, which may take a moment
+    Wait::default().until(|| (tab.blocked_request_counts().by_url == 2).then_some(()))?;
+    assert_eq!(
+        tab.blocked_request_counts(),
+        BlockedRequestCounts {
+            by_url: 2,
+            by_resource_type: 1,
+        }
+    );
+    assert_eq!(tab.blocked_request_counts().total(), 3);
+    Ok(())
+}
+
+#[test]
+fn route_response_patches_and_replaces_responses() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    let patch = tab.route_response("**/api/**", |response| {
+        assert_eq!(response.status(), Some(200));
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        let text = response.text()?;
+        response.fulfill_body(format!("{text} (patched)"))
+    })?;
+    tab.route_response("**/api/passthrough", |response| {
+        response.continue_response()
+    })?;
+    tab.route_response("**/api/gone", |response| {
+        response.fulfill(404, &[("Content-Type", "text/plain")], "gone")
+    })?;
+
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user (patched)");
+    assert_eq!(
+        fetch_text(&tab, "/api/passthrough")?,
+        "GET /api/passthrough"
+    );
+    let status: u32 = tab.call_function(
+        "path => fetch(path).then(response => response.status)",
+        ("/api/gone",),
+    )?;
+    assert_eq!(status, 404);
+
+    // a request answered by a request route never reaches the response stage
+    tab.route("**/api/mocked", |route| route.fulfill(200, &[], "mocked"))?;
+    assert_eq!(fetch_text(&tab, "/api/mocked")?, "mocked");
+
+    assert!(tab.unroute(patch)?);
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user");
+    Ok(())
+}
+
+#[test]
+fn route_response_with_all_requests_intercepted() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    tab.enable_fetch(None, None)?;
+    tab.route_response("**/api/**", |response| {
+        let text = response.text()?;
+        response.fulfill_body(format!("{text} (patched)"))
+    })?;
+    assert_eq!(fetch_text(&tab, "/api/user")?, "GET /api/user (patched)");
+    assert_eq!(fetch_text(&tab, "/other")?, "GET /other");
+    Ok(())
+}
+
+#[test]
+fn route_response_without_upstream_response() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+    let closed_port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+
+    let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let statuses_clone = Arc::clone(&statuses);
+    tab.route_response("**/refused", move |response| {
+        statuses_clone.lock().unwrap().push(response.status());
+        // there is no status to keep, so this fails and the request fails as it would have
+        response.fulfill_body("patched")
+    })?;
+    let url = format!("http://127.0.0.1:{closed_port}/refused");
+    assert_eq!(fetch_text(&tab, &url)?, "failed");
+    assert_eq!(*statuses.lock().unwrap(), [None]);
+    Ok(())
+}
+
+#[test]
+fn logs_request_lifecycles() -> Result<()> {
+    let server = echo_server();
+    let (_browser, tab) = navigate_to_server(&server)?;
+
+    let log = tab.start_network_log(NetworkLogOptions { max_records: 3 })?;
+    fetch_text(&tab, "/api/user")?;
+    fetch_text(&tab, "/redirect")?;
+    tab.route("**/blocked", |route| route.abort(ErrorReason::Failed))?;
+    fetch_text(&tab, "/blocked")?;
+    Wait::default().until(|| {
+        log.records()
+            .iter()
+            .all(|record| record.is_complete())
+            .then_some(())
+    })?;
+
+    let records = log.records();
+    assert_eq!(records.len(), 3);
+    let user = &records[0];
+    assert_eq!(user.request.url, format!("{}/api/user", server.url()));
+    assert_eq!(user.status(), Some(200));
+    assert_eq!(user.resource_type, Some(ResourceType::Fetch));
+    assert!(user.encoded_data_length.unwrap() > 0.0);
+    assert!(user.duration().is_some());
+
+    let redirect = &records[1];
+    assert_eq!(
+        redirect.original_url(),
+        format!("{}/redirect", server.url())
+    );
+    assert_eq!(redirect.request.url, format!("{}/target", server.url()));
+    assert_eq!(redirect.redirects.len(), 1);
+    assert_eq!(redirect.redirects[0].response.status, 302);
+    assert_eq!(redirect.status(), Some(200));
+
+    let failed = log.filter(&RequestFilter {
+        failed: Some(true),
+        ..Default::default()
+    });
+    assert_eq!(failed.len(), 1);
+    assert_eq!(
+        failed[0].failure.as_ref().unwrap().error_text,
+        "net::ERR_FAILED"
+    );
+    assert_eq!(
+        log.find_last(&RequestFilter {
+            url: Some("**/api/*".into()),
+            ..Default::default()
+        })
+        .map(|record| record.request_id),
+        Some(user.request_id.clone())
+    );
+
+    fetch_text(&tab, "/api/more")?;
+    assert_eq!(log.len(), 3);
+    assert_eq!(log.evicted_count(), 1);
+
+    log.clear();
+    assert!(log.is_empty());
+    tab.stop_network_log()?;
+    assert!(tab.network_log().is_none());
+    fetch_text(&tab, "/api/user")?;
+    assert!(log.is_empty());
+    Ok(())
+}
+
+#[test]
+fn controls_cache_and_origin_data() -> Result<()> {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let requests_clone = Arc::clone(&requests);
+    let server = server::Server::new(move |request: tiny_http::Request| {
+        let count = requests_clone.fetch_add(1, Ordering::SeqCst) + 1;
+        let header =
+            tiny_http::Header::from_bytes(&b"Cache-Control"[..], &b"max-age=3600"[..]).unwrap();
+        request.respond(tiny_http::Response::from_string(count.to_string()).with_header(header))
+    });
+    let (_browser, tab) = navigate_to_server(&server)?;
+    let served = || requests.load(Ordering::SeqCst);
+
+    let first = fetch_text(&tab, "/cached")?;
+    assert_eq!(fetch_text(&tab, "/cached")?, first);
+    let before = served();
+
+    tab.set_cache_disabled(true)?;
+    fetch_text(&tab, "/cached")?;
+    assert_eq!(served(), before + 1);
+    tab.set_cache_disabled(false)?;
+
+    tab.clear_browser_cache()?;
+    fetch_text(&tab, "/cached")?;
+    assert_eq!(served(), before + 2);
+    fetch_text(&tab, "/cached")?;
+    assert_eq!(served(), before + 2);
+
+    tab.evaluate("localStorage.setItem('key', 'value')", false)?;
+    tab.clear_data_for_origin(&server.url(), &[StorageType::LocalStorage])?;
+    let item: Option<String> = tab.evaluate_typed("localStorage.getItem('key')")?;
+    assert_eq!(item, None);
+    Ok(())
+}
+
+/// A service worker answering `/sw-path` itself, and controlling its page as soon as possible.
+const SERVICE_WORKER: &str = "
+    self.addEventListener('install', () => self.skipWaiting());
+    self.addEventListener('activate', event => event.waitUntil(clients.claim()));
+    self.addEventListener('fetch', event => {
+        if (new URL(event.request.url).pathname === '/sw-path') {
+            event.respondWith(new Response('from worker'));
+        }
+    });
+";
+
+#[test]
+fn bypasses_service_workers() -> Result<()> {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let requests_clone = Arc::clone(&requests);
+    let server = server::Server::new(move |request: tiny_http::Request| {
+        let (body, content_type) = match request.url() {
+            "/sw.js" => (SERVICE_WORKER, "text/javascript"),
+            "/sw-path" => {
+                requests_clone.fetch_add(1, Ordering::SeqCst);
+                ("from server", "text/plain")
+            }
+            _ => ("<html><body></body></html>", "text/html"),
+        };
+        let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes());
+        request.respond(tiny_http::Response::from_string(body).with_header(header.unwrap()))
+    });
+    let (_browser, tab) = navigate_to_server(&server)?;
+    let controlled: bool = tab.evaluate_typed(
+        "navigator.serviceWorker.register('/sw.js').then(() => new Promise(resolve => {
+            if (navigator.serviceWorker.controller) {
+                resolve(true);
+            }
+            navigator.serviceWorker.oncontrollerchange = () => resolve(true);
+        }))",
+    )?;
+    assert!(controlled);
+
+    assert_eq!(fetch_text(&tab, "/sw-path")?, "from worker");
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+    tab.set_bypass_service_worker(true)?;
+    assert_eq!(fetch_text(&tab, "/sw-path")?, "from server");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    tab.set_bypass_service_worker(false)?;
+    assert_eq!(fetch_text(&tab, "/sw-path")?, "from worker");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+/// A forward proxy which answers every request itself with its `name` and the requested URL,
+/// after asking for `user:secret` credentials.
+fn proxy_server(name: &'static str) -> server::Server {
+    server::Server::new(move |request: tiny_http::Request| {
+        let authorized = request.headers().iter().any(|header| {
+            header.field.equiv("Proxy-Authorization") && header.value == "Basic dXNlcjpzZWNyZXQ="
+        });
+        if !authorized {
+            let challenge = tiny_http::Header::from_bytes(
+                &b"Proxy-Authenticate"[..],
+                &b"Basic realm=\"proxy\""[..],
+            );
+            return request
+                .respond(tiny_http::Response::empty(407).with_header(challenge.unwrap()));
+        }
+        let body = format!("{name} {}", request.url());
+        let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/plain"[..]);
+        request.respond(tiny_http::Response::from_string(body).with_header(header.unwrap()))
+    })
+}
+
+#[test]
+fn contexts_use_their_own_proxies() -> Result<()> {
+    let first_proxy = proxy_server("first");
+    let second_proxy = proxy_server("second");
+    let browser = Browser::default()?;
+
+    for (proxy, name) in [(&first_proxy, "first"), (&second_proxy, "second")] {
+        let context = browser.new_context_with_options(
+            ContextOptions::default_builder()
+                .proxy_server(Some(proxy.url()))
+                .proxy_credentials(Some(("user".to_string(), "secret".to_string())))
+                .build()?,
+        )?;
+        let tab = context.new_tab()?;
+        tab.navigate_to("http://crawled.test/page")?
+            .wait_until_navigated()?;
+        let text: String = tab.evaluate_typed("document.body.innerText")?;
+        assert_eq!(text, format!("{name} http://crawled.test/page"));
+
+        // the credentials outlive changes to the tab's own interception
+        tab.enable_fetch(None, None)?.disable_fetch()?;
+        tab.navigate_to("http://crawled.test/again")?
+            .wait_until_navigated()?;
+        let text: String = tab.evaluate_typed("document.body.innerText")?;
+        assert_eq!(text, format!("{name} http://crawled.test/again"));
+
+        // and apply to the pages it opens
+        tab.evaluate("window.open('about:blank')", false)?;
+        let popup = Wait::default().until(|| {
+            context
+                .get_tabs()
+                .ok()?
+                .into_iter()
+                .find(|other| *other.get_target_id() != *tab.get_target_id())
+        })?;
+        popup
+            .navigate_to("http://crawled.test/popup")?
+            .wait_until_navigated()?;
+        let text: String = popup.evaluate_typed("document.body.innerText")?;
+        assert_eq!(text, format!("{name} http://crawled.test/popup"));
+    }
+    Ok(())
+}
+
+#[test]
+fn context_options_debug_hides_proxy_password() -> Result<()> {
+    let options = ContextOptions::default_builder()
+        .proxy_credentials(Some(("user".to_string(), "secret".to_string())))
+        .build()?;
+    let debug = format!("{options:?}");
+    assert!(debug.contains("user") && !debug.contains("secret"));
+    Ok(())
+}
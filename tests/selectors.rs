@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use headless_chrome::Browser;
+
+mod server;
+
+#[test]
+fn find_by_text_role_label_placeholder_and_test_id() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("selectors.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    let id_of = |element: headless_chrome::Element| -> Result<Option<String>> {
+        element.get_attribute_value("id")
+    };
+
+    assert_eq!(
+        id_of(tab.find_by_text("Hello, world!")?)?.unwrap(),
+        "greeting"
+    );
+    assert_eq!(
+        tab.find_by_text(r#"the "terms" & 'conditions'"#)?.tag_name,
+        "LABEL"
+    );
+
+    assert_eq!(
+        id_of(tab.find_by_role("button", Some("Create account"))?)?.unwrap(),
+        "fancy-submit"
+    );
+    assert_eq!(
+        id_of(tab.find_by_role("button", Some("Cancel"))?)?.unwrap(),
+        "cancel"
+    );
+    assert_eq!(tab.find_by_role("heading", None)?.tag_name, "H1");
+    assert!(tab.find_by_role("button", Some("Delete account")).is_err());
+
+    assert_eq!(
+        id_of(tab.find_by_label("Email address")?)?.unwrap(),
+        "email"
+    );
+    // the heading with the same name comes first, but isn't a form control
+    assert_eq!(id_of(tab.find_by_label("Password")?)?.unwrap(), "password");
+    assert_eq!(
+        tab.find_by_role("heading", Some("Password"))?.tag_name,
+        "H2"
+    );
+    assert_eq!(
+        id_of(tab.find_by_placeholder("you@example.com")?)?.unwrap(),
+        "email"
+    );
+    assert_eq!(
+        id_of(tab.find_by_test_id("submit-button")?)?.unwrap(),
+        "fancy-submit"
+    );
+    Ok(())
+}
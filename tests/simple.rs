@@ -22,6 +22,11 @@ use log::*;
 use rand::prelude::*;
 
 use headless_chrome::browser::tab::RequestPausedDecision;
+use headless_chrome::browser::tab::element::{
+    FillFormError, FormFieldError, FormValue, SelectOption,
+};
+use headless_chrome::browser::tab::js::JsException;
+use headless_chrome::browser::tab::network::UrlMatcher;
 use headless_chrome::browser::transport::{SessionId, Transport};
 use headless_chrome::util::Wait;
 use headless_chrome::{Browser, Tab};
@@ -184,6 +189,333 @@ fn form_interaction() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn fill_select_and_check_form_fields() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+    let value_of = |selector: &str| -> Result<serde_json::Value> {
+        Ok(tab
+            .evaluate(
+                &format!("document.querySelector('{selector}').value"),
+                false,
+            )?
+            .value
+            .unwrap())
+    };
+
+    let target = tab.wait_for_element("input#target")?;
+    target.type_into("moon")?;
+    target.fill("mothership")?;
+    assert_eq!(value_of("#target")?, "mothership");
+    tab.find_element("#orders")?.clear()?;
+    assert_eq!(value_of("#orders")?, "");
+    tab.find_element("#date")?.fill("2030-01-02")?;
+    assert_eq!(value_of("#date")?, "2030-01-02");
+
+    let site = tab.find_element("#site")?;
+    site.select_option(&SelectOption::Label("New Mexico".to_string()))?;
+    assert_eq!(value_of("#site")?, "nm");
+    site.select_option(&SelectOption::Index(1))?;
+    assert_eq!(value_of("#site")?, "nv");
+    assert!(
+        site.select_option(&SelectOption::Value("ak".to_string()))
+            .unwrap_err()
+            .is::<FormFieldError>()
+    );
+
+    let sneak = tab.find_element("#sneakattack")?;
+    sneak.check()?.check()?;
+    assert_eq!(
+        tab.evaluate("document.getElementById('sneakattack').checked", false)?
+            .value,
+        Some(true.into())
+    );
+    sneak.uncheck()?;
+    tab.find_element("#yield-high")?.check()?;
+    assert!(tab.find_element("#yield-high")?.uncheck().is_err());
+
+    assert_eq!(
+        tab.evaluate("window.changes.join()", false)?.value,
+        Some("target,orders,date,site,site,sneakattack,sneakattack,yield-high".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn fill_form_reports_failing_fields() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+    tab.wait_for_element("input#target")?;
+
+    tab.fill_form([
+        ("#target", FormValue::from("mothership")),
+        ("#site", "New Mexico".into()),
+        ("#sneakattack", true.into()),
+    ])?;
+    tab.find_element("button")?.click()?;
+    let d = tab.wait_for_element("div#protocol")?.get_description()?;
+    assert!(
+        d.find(|n| n.node_value == "Comrades, have a nice day!")
+            .is_some()
+    );
+
+    let Err(error) = tab.fill_form(HashMap::from([
+        ("#orders", FormValue::from("Launch")),
+        ("#codes", "1234".into()),
+        ("#missing", "nothing".into()),
+    ])) else {
+        panic!("filling a disabled and a missing field should fail");
+    };
+    let mut failed: Vec<String> = error
+        .downcast::<FillFormError>()?
+        .failures
+        .into_iter()
+        .map(|(selector, _)| selector)
+        .collect();
+    failed.sort();
+    assert_eq!(failed, ["#codes", "#missing"]);
+    assert_eq!(
+        tab.evaluate("document.getElementById('orders').value", false)?
+            .value,
+        Some("Launch".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn element_state_queries() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("element_state.html"));
+
+    let text = tab.wait_for_element("#text")?;
+    assert!(text.is_enabled()? && text.is_editable()? && !text.is_focused()?);
+    text.type_into("!")?;
+    assert!(text.is_focused()?);
+    assert_eq!(text.input_value()?, "initial!");
+    assert_eq!(text.value, "initial");
+    assert!(text.is_checked().is_err());
+
+    assert!(!tab.find_element("#readonly")?.is_editable()?);
+    assert!(!tab.find_element("#in-fieldset")?.is_enabled()?);
+    assert!(tab.find_element("#editable")?.is_editable()?);
+    assert!(tab.find_element("#box")?.is_editable().is_err());
+    assert!(tab.find_element("#checkbox")?.is_checked()?);
+    assert!(!tab.find_element("#aria-checkbox")?.is_checked()?);
+
+    let bounding_box = tab.find_element("#box")?.bounding_box()?.unwrap();
+    assert_eq!(
+        (
+            bounding_box.x,
+            bounding_box.y,
+            bounding_box.width,
+            bounding_box.height
+        ),
+        (10.0, 20.0, 100.0, 50.0)
+    );
+    assert!(tab.find_element("#hidden")?.bounding_box()?.is_none());
+
+    assert!(tab.find_element("#box")?.is_in_viewport(1.0)?);
+    assert!(!tab.find_element("#below")?.is_in_viewport(0.0)?);
+    let half = tab.find_element("#half")?;
+    assert!(half.is_in_viewport(0.4)? && !half.is_in_viewport(0.6)?);
+
+    let checkbox = tab.find_element("#checkbox")?;
+    assert!(checkbox.get_property::<bool>("checked")?);
+    assert_eq!(
+        tab.find_element("#box")?
+            .get_property::<HashMap<String, String>>("dataset")?["kind"],
+        "box"
+    );
+    assert_eq!(
+        checkbox.get_property::<Option<String>>("noSuchProperty")?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn navigate_element_hierarchy() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("dom.html"));
+    let id_of = |element: Option<headless_chrome::Element>| -> Result<Option<String>> {
+        element.map_or(Ok(None), |e| e.get_attribute_value("id"))
+    };
+
+    let second = tab.wait_for_element("#second")?;
+    assert_eq!(id_of(second.parent()?)?.as_deref(), Some("list"));
+    assert_eq!(id_of(second.next_sibling()?)?.as_deref(), Some("third"));
+    assert_eq!(id_of(second.previous_sibling()?)?.as_deref(), Some("first"));
+    assert!(tab.find_element("#third")?.next_sibling()?.is_none());
+    assert!(tab.find_element("html")?.parent()?.is_none());
+
+    let children = tab.find_element("#list")?.children()?;
+    let ids: Vec<_> = children
+        .into_iter()
+        .map(|e| e.get_attribute_value("id"))
+        .collect::<Result<_>>()?;
+    assert_eq!(
+        ids,
+        [
+            Some("first".into()),
+            Some("second".into()),
+            Some("third".into())
+        ]
+    );
+
+    let bold = tab.find_element("#bold")?;
+    assert_eq!(id_of(bold.closest(".card")?)?.as_deref(), Some("section"));
+    assert_eq!(id_of(bold.closest("li")?)?.as_deref(), Some("second"));
+    assert!(bold.closest("table")?.is_none());
+
+    let shadow_root = tab.find_element("#host")?.shadow_root()?.unwrap();
+    assert_eq!(
+        shadow_root.find_element("#shadowed")?.get_inner_text()?,
+        "In the shadows"
+    );
+    assert!(bold.shadow_root()?.is_none());
+
+    let frame = tab.find_element("#frame")?;
+    let frame_document = Wait::default().until(|| {
+        frame
+            .content_frame()
+            .ok()
+            .flatten()
+            .filter(|document| document.find_element("#inside").is_ok())
+    })?;
+    assert_eq!(
+        frame_document.find_element("#inside")?.get_inner_text()?,
+        "Inside the frame"
+    );
+    assert!(bold.content_frame()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn mutate_dom() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("dom.html"));
+    let list = tab.wait_for_element("#list")?;
+
+    // node IDs are invalidated by this, but mutations still have to work
+    tab.find_element("#first")?.remove()?;
+    list.set_attribute("data-state", "loaded")?;
+    assert!(tab.find_element("ul[data-state=loaded]").is_ok());
+    list.remove_attribute("data-state")?;
+    assert!(tab.find_element("ul[data-state]").is_err());
+    assert_eq!(tab.find_elements("#list > li")?.len(), 2);
+
+    tab.find_element("#third")?
+        .set_outer_html("<li id='replaced'>Replaced</li>")?;
+    assert_eq!(
+        tab.find_element("#list > li:last-child")?
+            .get_inner_text()?,
+        "Replaced"
+    );
+
+    tab.find_element_by_xpath("//p[@id='text']/text()")?
+        .set_node_value("Other text")?;
+    assert_eq!(tab.find_element("#text")?.get_inner_text()?, "Other text");
+    Ok(())
+}
+
+#[test]
+fn typed_js_evaluation() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("dom.html"));
+    tab.wait_for_element("#list")?;
+
+    let ids: Vec<String> = tab
+        .evaluate_typed("Promise.resolve([...document.querySelectorAll('li')].map(li => li.id))")?;
+    assert_eq!(ids, ["first", "second", "third"]);
+    let nothing: Option<u32> = tab.evaluate_typed("undefined")?;
+    assert_eq!(nothing, None);
+
+    let sum: f64 = tab.call_function("(a, b) => a + b", (1.5, 2))?;
+    assert!((sum - 3.5).abs() < f64::EPSILON);
+    let keys: Vec<String> = tab.call_function(
+        "o => Object.keys(o)",
+        (serde_json::json!({"b": 1, "a": {"nested": true}}),),
+    )?;
+    assert_eq!(keys, ["b", "a"]);
+    // every element of the tuple is exactly one argument
+    let lengths: Vec<u32> =
+        tab.call_function("(...args) => args.map(a => a?.length ?? -1)", (vec![1, 2],))?;
+    assert_eq!(lengths, [2]);
+    let is_null: bool = tab.call_function("a => a === null", (None::<u32>,))?;
+    assert!(is_null);
+
+    // elements and handles are passed by reference
+    let list = tab.find_element("#list")?;
+    let bold = tab.find_element("#bold")?;
+    let contains: bool =
+        list.call_function("function(other) { return this.contains(other); }", (&bold,))?;
+    assert!(contains);
+    let section = tab.evaluate_handle("document.getElementById('section')")?;
+    let tag: String = tab.call_function("(el, suffix) => el.tagName + suffix", (&section, "!"))?;
+    assert_eq!(tag, "SECTION!");
+    assert!(
+        tab.call_function::<_, String>("els => els[0].tagName", (vec![&section],))
+            .is_err()
+    );
+    assert_eq!(
+        section.as_element()?.map(|element| element.tag_name),
+        Some("SECTION".to_string())
+    );
+    let size: usize = tab
+        .evaluate_handle("new Map([[1, 2]])")?
+        .call_function("function() { return this.size; }", ())?;
+    assert_eq!(size, 1);
+
+    let error = tab
+        .evaluate_typed::<()>("null.foo")
+        .expect_err("evaluating should throw");
+    let exception = error.downcast::<JsException>()?;
+    assert!(exception.message.starts_with("TypeError"), "{exception:?}");
+    assert_eq!((exception.line_number, exception.column_number), (0, 5));
+
+    let error = list
+        .call_function::<_, ()>("function() {\n  throw new RangeError('too far');\n}", ())
+        .expect_err("calling should throw");
+    let exception = error.downcast::<JsException>()?;
+    assert_eq!(exception.message, "RangeError: too far");
+    assert_eq!(exception.line_number, 1);
+    assert!(exception.stack.is_some_and(|stack| stack.contains("at ")));
+
+    let error = tab
+        .call_function::<_, ()>("() => { throw 'plain string'; }", ())
+        .expect_err("calling should throw");
+    assert_eq!(error.downcast::<JsException>()?.message, "plain string");
+    Ok(())
+}
+
+#[test]
+fn element_handle_outlives_tab_borrow() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+
+    struct Form {
+        target: headless_chrome::ElementHandle,
+    }
+
+    let form = Form {
+        target: tab.wait_for_element_handle("input#target")?,
+    };
+    // requesting the document again invalidates the node IDs of existing elements
+    tab.find_element("button")?;
+
+    std::thread::spawn(move || form.target.element()?.type_into("mothership").map(|_| ()))
+        .join()
+        .unwrap()?;
+
+    tab.find_element_handle("button")?.element()?.click()?;
+    let d = tab.wait_for_element("div#protocol")?.get_description()?;
+    assert!(
+        d.find(|n| n.node_value == "Missiles launched against mothership")
+            .is_some()
+    );
+    Ok(())
+}
+
 #[test]
 fn send_character() -> Result<()> {
     logging::enable_logging();
@@ -716,6 +1048,44 @@ fn response_handler() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn wait_for_request_and_response() -> Result<()> {
+    logging::enable_logging();
+    let (server, browser, tab) = dumb_server(include_str!("simple.html"));
+    tab.wait_until_navigated()?;
+
+    let (request, response) = std::thread::scope(|s| {
+        let request = s.spawn(|| tab.wait_for_request("**/api/*"));
+        let response = s.spawn(|| tab.wait_for_response(regex::Regex::new(r"/api/save$").unwrap()));
+        // the waiters only see requests made after they've registered, which can't be
+        // observed from here, so keep making requests until both have seen one
+        while !(request.is_finished() && response.is_finished()) {
+            tab.evaluate(
+                "fetch('/api/save', { method: 'POST', body: 'data' })",
+                false,
+            )
+            .unwrap();
+            sleep(Duration::from_millis(50));
+        }
+        (request.join().unwrap(), response.join().unwrap())
+    });
+
+    let request = request?;
+    assert_eq!(request.request.method, "POST");
+    assert!(request.request.url.ends_with("/api/save"));
+
+    let response = response?;
+    assert_eq!(response.params.response.status, 200);
+    assert!(response.body()?.body.contains("foobar"));
+
+    let timed_out = tab.wait_for_response_with_custom_timeout(
+        UrlMatcher::predicate(|url| url.ends_with("/never")),
+        Duration::from_millis(200),
+    );
+    assert!(timed_out.is_err());
+    Ok(())
+}
+
 #[test]
 fn loading_failed_handler() -> Result<()> {
     logging::enable_logging();
@@ -939,6 +1309,36 @@ fn parses_shadow_doms() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn find_elements_in_shadow_doms() -> Result<()> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("shadow-dom.html"));
+    tab.wait_for_element("html")?;
+
+    assert!(tab.find_element("#shadow-input").is_err());
+
+    tab.find_element_deep("#shadow-input")?
+        .type_into("from the shadows")?;
+    tab.find_element_deep("#has-open >>> button")?.click()?;
+    assert_eq!(
+        tab.find_element("#typed")?.get_inner_text()?,
+        "from the shadows"
+    );
+
+    // only the span in the open (nested) shadow root is reachable, not the one in the closed root
+    let deep_spans = tab.find_elements_deep("span.deep")?;
+    assert_eq!(deep_spans.len(), 1);
+    assert_eq!(deep_spans[0].get_inner_text()?, "nested");
+
+    let host = tab.find_element("#has-open")?;
+    assert_eq!(host.find_elements_deep("#nested-host >>> .deep")?.len(), 1);
+    // nothing matching is an error for tabs and elements alike
+    assert!(host.find_elements_deep(".missing").is_err());
+    assert!(tab.find_elements_deep(".missing").is_err());
+    assert!(tab.find_element_deep("#has-closed >>> .deep").is_err());
+    Ok(())
+}
+
 #[test]
 fn set_extra_http_headers() -> Result<()> {
     let (server, browser, tab) = dumb_server(include_str!("simple.html"));
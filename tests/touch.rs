@@ -0,0 +1,86 @@
+use anyhow::Result;
+
+use headless_chrome::Browser;
+use headless_chrome::browser::tab::SwipeDirection;
+
+mod server;
+
+fn evaluate_json(tab: &headless_chrome::Tab, expression: &str) -> Result<serde_json::Value> {
+    let json = tab
+        .evaluate(&format!("JSON.stringify({expression})"), false)?
+        .value
+        .unwrap();
+    Ok(serde_json::from_str(json.as_str().unwrap())?)
+}
+
+#[test]
+fn tap_and_long_press() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("touch.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    assert_eq!(evaluate_json(&tab, "navigator.maxTouchPoints")?, 0);
+
+    let button = tab.wait_for_element("#button")?;
+    button.tap()?;
+    assert_eq!(evaluate_json(&tab, "navigator.maxTouchPoints")?, 5);
+    let events = headless_chrome::util::Wait::default().until(|| {
+        let events = evaluate_json(&tab, "window.events").ok()?;
+        events
+            .as_array()?
+            .contains(&serde_json::json!("click"))
+            .then_some(events)
+    })?;
+    assert_eq!(
+        events,
+        serde_json::json!(["touchstart", "touchend", "click"])
+    );
+
+    tab.evaluate("window.events = []", false)?;
+    button.long_press()?;
+    let events = evaluate_json(&tab, "window.events")?;
+    assert!(
+        events
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("touchstart"))
+    );
+    assert!(
+        !events
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("click"))
+    );
+    Ok(())
+}
+
+#[test]
+fn swipe_dispatches_touch_moves() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("touch.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    tab.wait_for_element("#carousel")?
+        .swipe(SwipeDirection::Left, 100.0)?;
+
+    let swipe = evaluate_json(&tab, "window.swipe")?;
+    assert_eq!(swipe["moves"], 10);
+    assert_eq!(swipe["dx"], -100);
+    assert_eq!(swipe["ended"], true);
+    Ok(())
+}
+
+#[test]
+fn pinch_zooms_the_page() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("touch.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    tab.wait_for_element("#carousel")?.pinch(2.0)?;
+    let scale = evaluate_json(&tab, "window.visualViewport.scale")?;
+    assert!(scale.as_f64().unwrap() > 1.5);
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use headless_chrome::Browser;
+use headless_chrome::browser::tab::websocket::{FrameDirection, WebSocket, WebSocketRoute};
+use headless_chrome::util::Wait;
+
+mod server;
+
+/// Starts a WebSocket server which echoes the messages of its first client, and returns its
+/// port.
+fn echo_websocket_server() -> Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        while let Ok(message) = socket.read() {
+            if message.is_text() {
+                socket.send(message).unwrap();
+            }
+        }
+    });
+    Ok(port)
+}
+
+#[test]
+fn inspects_websocket_frames() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("simple.html"));
+    let port = echo_websocket_server()?;
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    let sockets: Arc<Mutex<Vec<WebSocket>>> = Arc::default();
+    let sockets_clone = Arc::clone(&sockets);
+    let closed = Arc::new(AtomicBool::new(false));
+    let closed_clone = Arc::clone(&closed);
+    tab.on_websocket(move |socket| {
+        let closed = Arc::clone(&closed_clone);
+        socket.on_close(move || closed.store(true, Ordering::SeqCst));
+        sockets_clone.lock().unwrap().push(socket.clone());
+    })?;
+
+    let echo: String = tab.call_function(
+        "url => new Promise(resolve => {
+            window.socket = new WebSocket(url);
+            socket.onopen = () => socket.send('hello');
+            socket.onmessage = event => resolve(event.data);
+        })",
+        (format!("ws://127.0.0.1:{port}/echo"),),
+    )?;
+    assert_eq!(echo, "hello");
+
+    let socket = sockets.lock().unwrap()[0].clone();
+    assert_eq!(socket.url(), format!("ws://127.0.0.1:{port}/echo"));
+    let received = socket.wait_for_frame(|frame| frame.direction == FrameDirection::Received)?;
+    assert_eq!(received.payload, "hello");
+    assert!(received.is_text());
+    assert_eq!(socket.handshake_response().unwrap().status, 101);
+    let payloads: Vec<_> = socket
+        .frames()
+        .into_iter()
+        .map(|frame| (frame.direction, frame.payload))
+        .collect();
+    assert_eq!(
+        payloads,
+        [
+            (FrameDirection::Sent, "hello".to_string()),
+            (FrameDirection::Received, "hello".to_string())
+        ]
+    );
+
+    tab.evaluate("socket.close()", false)?;
+    Wait::default().until(|| closed.load(Ordering::SeqCst).then_some(()))?;
+    assert!(socket.is_closed());
+    Ok(())
+}
+
+#[test]
+fn mocks_websockets() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("simple.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    let closed_by_page = Arc::new(AtomicBool::new(false));
+    let closed_clone = Arc::clone(&closed_by_page);
+    let route = tab.route_websocket("ws://mock.test/**", move |socket| {
+        assert_eq!(socket.url(), "ws://mock.test/chat");
+        let server = socket.clone();
+        socket.on_message(move |message| {
+            server.send(&message.to_uppercase()).unwrap();
+        });
+        let closed = Arc::clone(&closed_clone);
+        socket.on_close(move || closed.store(true, Ordering::SeqCst));
+        socket.send("welcome")
+    })?;
+
+    let messages: Vec<String> = tab.evaluate_typed(
+        "new Promise(resolve => {
+            const messages = [];
+            window.socket = new WebSocket('ws://mock.test/chat');
+            socket.addEventListener('open', () => socket.send('ping'));
+            socket.onmessage = event => {
+                messages.push(event.data);
+                if (messages.length === 2) {
+                    resolve(messages);
+                }
+            };
+        })",
+    )?;
+    assert_eq!(messages, ["welcome", "PING"]);
+
+    let close_code: u32 = tab.evaluate_typed(
+        "new Promise(resolve => {
+            socket.onclose = event => resolve(event.code);
+            socket.close(4000);
+        })",
+    )?;
+    assert_eq!(close_code, 4000);
+    Wait::default().until(|| closed_by_page.load(Ordering::SeqCst).then_some(()))?;
+
+    // the server can close the socket, too
+    tab.route_websocket("ws://mock.test/closing", |socket| {
+        socket.close(1001, "going away")
+    })?;
+    let reason: String = tab.evaluate_typed(
+        "new Promise(resolve => {
+            new WebSocket('ws://mock.test/closing').onclose = event => resolve(event.reason);
+        })",
+    )?;
+    assert_eq!(reason, "going away");
+
+    // without a matching route, sockets connect to the network
+    assert!(tab.unroute(route)?);
+    let failed: bool = tab.evaluate_typed(
+        "new Promise(resolve => {
+            const socket = new WebSocket('ws://127.0.0.1:1/chat');
+            socket.onerror = () => resolve(true);
+            socket.onopen = () => resolve(false);
+        })",
+    )?;
+    assert!(failed);
+    Ok(())
+}
+
+#[test]
+fn mocked_websockets_belong_to_the_top_level_document() -> Result<()> {
+    let server = server::Server::with_dumb_html(include_str!("simple.html"));
+    let browser = Browser::default()?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+
+    let sockets: Arc<Mutex<Vec<WebSocketRoute>>> = Arc::default();
+    let sockets_clone = Arc::clone(&sockets);
+    tab.route_websocket("ws://mock.test/**", move |socket| {
+        sockets_clone.lock().unwrap().push(socket.clone());
+        Ok(())
+    })?;
+    let open_socket = || -> Result<()> {
+        tab.evaluate(
+            "window.received = [];
+            new WebSocket('ws://mock.test/chat').onmessage = event => received.push(event.data);",
+            false,
+        )?;
+        Ok(())
+    };
+
+    open_socket()?;
+    Wait::default().until(|| (sockets.lock().unwrap().len() == 1).then_some(()))?;
+    let old_socket = sockets.lock().unwrap()[0].clone();
+    let closed = Arc::new(AtomicBool::new(false));
+    let closed_clone = Arc::clone(&closed);
+    old_socket.on_close(move || closed_clone.store(true, Ordering::SeqCst));
+
+    // navigating away closes the sockets, and their IDs aren't reused by the next document
+    tab.navigate_to(&server.url())?.wait_until_navigated()?;
+    Wait::default().until(|| closed.load(Ordering::SeqCst).then_some(()))?;
+    open_socket()?;
+    Wait::default().until(|| (sockets.lock().unwrap().len() == 2).then_some(()))?;
+    let new_socket = sockets.lock().unwrap()[1].clone();
+    old_socket.send("stale")?;
+    new_socket.send("fresh")?;
+    let received: Vec<String> = tab.evaluate_typed("received")?;
+    assert_eq!(received, ["fresh"]);
+
+    // iframes keep the native WebSocket
+    let mocked_in_frame: bool = tab.evaluate_typed(
+        "new Promise(resolve => {
+            const frame = document.createElement('iframe');
+            frame.src = '/frame';
+            frame.onload = () => resolve('__headlessChromeWebSockets' in frame.contentWindow);
+            document.body.append(frame);
+        })",
+    )?;
+    assert!(!mocked_in_frame);
+    Ok(())
+}